@@ -0,0 +1,524 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::background::{parse_color, to_hex};
+use crate::blend::Blend;
+use crate::circle::{ColorMode, Position, RadiusChange, Universe};
+use crate::stroke::{Dash, Stroke, StrokeColor};
+
+// the values a slider can change
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Parameter {
+    DISTANCE,
+    COLOR,
+    RADIUS,
+    STEPS,
+    SPLIT,
+    CHILDREN,
+    APPLES,
+    TICKS,
+    BUDGET,
+}
+
+const ALL_PARAMETERS: [Parameter; 9] = [
+    Parameter::DISTANCE,
+    Parameter::COLOR,
+    Parameter::RADIUS,
+    Parameter::STEPS,
+    Parameter::SPLIT,
+    Parameter::CHILDREN,
+    Parameter::APPLES,
+    Parameter::TICKS,
+    Parameter::BUDGET,
+];
+
+impl Parameter {
+    pub fn name(self) -> &'static str {
+        match self {
+            Parameter::DISTANCE => "distance",
+            Parameter::COLOR => "color",
+            Parameter::RADIUS => "radius",
+            Parameter::STEPS => "steps",
+            Parameter::SPLIT => "split",
+            Parameter::CHILDREN => "children",
+            Parameter::APPLES => "apples",
+            Parameter::TICKS => "ticks",
+            Parameter::BUDGET => "budget",
+        }
+    }
+
+    pub fn of_name(name: &str) -> Option<Parameter> {
+        ALL_PARAMETERS
+            .iter()
+            .copied()
+            .find(|parameter| parameter.name() == name)
+    }
+
+    fn set(self, universe: &mut Universe, value: f64) {
+        match self {
+            Parameter::DISTANCE => universe.circle_config.max_position_delta = value,
+            Parameter::COLOR => universe.circle_config.max_color_delta = value as u8,
+            Parameter::RADIUS => universe.config.radius = value,
+            // an apple counts its steps down, and one with none left would go below 0
+            Parameter::STEPS => universe.config.apple_steps = u32::max(1, value as u32),
+            Parameter::SPLIT => universe.config.apple_split_chance = value,
+            // splitting into fewer than two would shrink the population it counts
+            Parameter::CHILDREN => universe.config.apple_children = u32::max(2, value as u32),
+            Parameter::APPLES => universe.config.max_apples = value as u32,
+            Parameter::TICKS => universe.config.speed.ticks = value,
+            Parameter::BUDGET => universe.config.speed.budget = value,
+        }
+    }
+}
+
+// something about one circle that can be different from the rest
+#[derive(Clone, Debug, PartialEq)]
+pub enum Override {
+    DISTANCE(Option<f64>),
+    COLOR(Option<u8>),
+    RADIUS(RadiusChange),
+    COLORMODE(ColorMode),
+    // back to doing what everyone else does
+    RESET,
+}
+
+fn write_optional<T: Display>(f: &mut fmt::Formatter<'_>, value: &Option<T>) -> fmt::Result {
+    match value {
+        Some(value) => write!(f, "{}", value),
+        None => write!(f, "none"),
+    }
+}
+
+impl Display for Override {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Override::DISTANCE(value) => {
+                write!(f, "distance ")?;
+                write_optional(f, value)
+            }
+            Override::COLOR(value) => {
+                write!(f, "color ")?;
+                write_optional(f, value)
+            }
+            Override::RADIUS(change) => write!(f, "radius {}", change.name()),
+            Override::COLORMODE(mode) => write!(f, "colormode {}", mode.name()),
+            Override::RESET => write!(f, "reset"),
+        }
+    }
+}
+
+fn parse_optional<T: FromStr>(word: Option<&str>) -> Result<Option<T>, String> {
+    match word {
+        Some("none") => Ok(None),
+        word => parse(word).map(Some),
+    }
+}
+
+fn parse_override<'a>(words: &mut impl Iterator<Item = &'a str>) -> Result<Override, String> {
+    match words.next() {
+        Some("distance") => Ok(Override::DISTANCE(parse_optional(words.next())?)),
+        Some("color") => Ok(Override::COLOR(parse_optional(words.next())?)),
+        Some("radius") => {
+            let name = words.next().unwrap_or("");
+            let change = RadiusChange::of_name(name)
+                .ok_or_else(|| format!("unknown radius change {:?}", name))?;
+            Ok(Override::RADIUS(change))
+        }
+        Some("colormode") => {
+            let name = words.next().unwrap_or("");
+            let mode =
+                ColorMode::of_name(name).ok_or_else(|| format!("unknown color mode {:?}", name))?;
+            Ok(Override::COLORMODE(mode))
+        }
+        Some("reset") => Ok(Override::RESET),
+        other => Err(format!("unknown override {:?}", other.unwrap_or(""))),
+    }
+}
+
+// a circle or an apple, by where it is in the universe's list
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Target {
+    CIRCLE(usize),
+    APPLE(usize),
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::CIRCLE(index) => write!(f, "circle {}", index),
+            Target::APPLE(index) => write!(f, "apple {}", index),
+        }
+    }
+}
+
+// a change typed into the inspector
+#[derive(Clone, Debug)]
+pub enum Edit {
+    POSITION(Position),
+    RADIUS(f64),
+    COLOR([u8; 3]),
+    // only apples have steps
+    STEPS(u32),
+}
+
+impl Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Edit::POSITION(position) => write!(f, "position {} {}", position.x, position.y),
+            Edit::RADIUS(radius) => write!(f, "radius {}", radius),
+            Edit::COLOR(rgb) => write!(f, "color {}", to_hex(*rgb)),
+            Edit::STEPS(steps) => write!(f, "steps {}", steps),
+        }
+    }
+}
+
+fn parse_edit<'a>(words: &mut impl Iterator<Item = &'a str>) -> Result<(Target, Edit), String> {
+    let target = match words.next() {
+        Some("circle") => Target::CIRCLE(parse(words.next())?),
+        Some("apple") => Target::APPLE(parse(words.next())?),
+        other => return Err(format!("unknown target {:?}", other.unwrap_or(""))),
+    };
+    let edit = match words.next() {
+        Some("position") => Edit::POSITION(parse_position(words)?),
+        Some("radius") => Edit::RADIUS(parse(words.next())?),
+        Some("color") => {
+            let word = words.next().unwrap_or("");
+            Edit::COLOR(parse_color(word).ok_or_else(|| format!("couldn't read {:?}", word))?)
+        }
+        Some("steps") => Edit::STEPS(parse(words.next())?),
+        other => return Err(format!("unknown edit {:?}", other.unwrap_or(""))),
+    };
+    Ok((target, edit))
+}
+
+// runs from paused for a while and pauses again, to watch a piece being built tick by tick
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Run {
+    TICKS(u64),
+    // until there are no apples left
+    APPLES,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    CIRCLE(Option<Position>),
+    APPLE(Option<Position>),
+    MOVE(usize, Position),
+    FREEZE,
+    TRASH,
+    PAUSE,
+    SPEED,
+    COLORMODE,
+    STROKE(Stroke),
+    BLEND(Blend),
+    SET(Parameter, f64),
+    OVERRIDE(usize, Override),
+    EDIT(Target, Edit),
+    RUN(Run),
+    RESIZE(f64, f64),
+}
+
+impl Action {
+    pub fn apply(self, universe: &mut Universe) {
+        match self {
+            Action::CIRCLE(None) => universe.add_circle(),
+            Action::CIRCLE(Some(position)) => universe.add_circle_at(position),
+            Action::APPLE(None) => universe.add_apple(),
+            Action::APPLE(Some(position)) => universe.add_apple_at(position),
+            Action::MOVE(index, position) => universe.move_circle(index, position),
+            Action::FREEZE => universe.circles.clear(),
+            Action::TRASH => {
+                universe.circles.clear();
+                universe.apples.clear();
+                universe.request_clear();
+            }
+            Action::PAUSE => universe.toggle_pause(),
+            Action::SPEED => universe.config.speed.toggle(),
+            Action::COLORMODE => universe.config.color_mode.toggle(),
+            Action::STROKE(stroke) => universe.config.stroke = stroke,
+            Action::BLEND(blend) => universe.config.blend = blend,
+            Action::SET(parameter, value) => parameter.set(universe, value),
+            Action::OVERRIDE(index, setting) => universe.override_circle(index, setting),
+            Action::EDIT(target, edit) => universe.edit(target, edit),
+            Action::RUN(run) => universe.run(run),
+            Action::RESIZE(width, height) => universe.resize(width, height),
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::CIRCLE(None) => write!(f, "circle"),
+            Action::CIRCLE(Some(position)) => write!(f, "circle {} {}", position.x, position.y),
+            Action::APPLE(None) => write!(f, "apple"),
+            Action::APPLE(Some(position)) => write!(f, "apple {} {}", position.x, position.y),
+            Action::MOVE(index, position) => {
+                write!(f, "move {} {} {}", index, position.x, position.y)
+            }
+            Action::FREEZE => write!(f, "freeze"),
+            Action::TRASH => write!(f, "trash"),
+            Action::PAUSE => write!(f, "pause"),
+            Action::SPEED => write!(f, "speed"),
+            Action::COLORMODE => write!(f, "colormode"),
+            Action::STROKE(stroke) => write!(
+                f,
+                "stroke {} {} {} {}",
+                stroke.width,
+                stroke.color.name(),
+                stroke.dash.name(),
+                if stroke.hollow { "hollow" } else { "filled" }
+            ),
+            Action::BLEND(blend) => write!(f, "blend {}", blend.name()),
+            Action::SET(parameter, value) => write!(f, "set {} {}", parameter.name(), value),
+            Action::OVERRIDE(index, setting) => write!(f, "override {} {}", index, setting),
+            Action::EDIT(target, edit) => write!(f, "edit {} {}", target, edit),
+            Action::RUN(Run::TICKS(ticks)) => write!(f, "run {}", ticks),
+            Action::RUN(Run::APPLES) => write!(f, "run apples"),
+            Action::RESIZE(width, height) => write!(f, "resize {} {}", width, height),
+        }
+    }
+}
+
+fn parse<T: FromStr>(word: Option<&str>) -> Result<T, String> {
+    let word = word.ok_or_else(|| String::from("missing value"))?;
+    word.parse::<T>()
+        .map_err(|_| format!("couldn't read {:?}", word))
+}
+
+fn parse_position<'a>(words: &mut impl Iterator<Item = &'a str>) -> Result<Position, String> {
+    Ok(Position::at(parse(words.next())?, parse(words.next())?))
+}
+
+fn parse_optional_position<'a>(
+    words: &mut std::iter::Peekable<impl Iterator<Item = &'a str>>,
+) -> Result<Option<Position>, String> {
+    match words.peek() {
+        None => Ok(None),
+        Some(_) => parse_position(words).map(Some),
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace().peekable();
+        match words.next() {
+            Some("circle") => Ok(Action::CIRCLE(parse_optional_position(&mut words)?)),
+            Some("apple") => Ok(Action::APPLE(parse_optional_position(&mut words)?)),
+            Some("move") => {
+                let index = parse(words.next())?;
+                Ok(Action::MOVE(index, parse_position(&mut words)?))
+            }
+            Some("freeze") => Ok(Action::FREEZE),
+            Some("trash") => Ok(Action::TRASH),
+            Some("pause") => Ok(Action::PAUSE),
+            Some("speed") => Ok(Action::SPEED),
+            Some("colormode") => Ok(Action::COLORMODE),
+            // logs from before strokes had a black outline checkbox
+            Some("bug") => Ok(Action::STROKE(if parse(words.next())? {
+                Stroke::of_color(StrokeColor::BLACK)
+            } else {
+                Stroke::new()
+            })),
+            Some("stroke") => {
+                let width = parse(words.next())?;
+                let name = words.next().unwrap_or("");
+                let color = StrokeColor::of_name(name)
+                    .ok_or_else(|| format!("unknown stroke color {:?}", name))?;
+                let name = words.next().unwrap_or("");
+                let dash = Dash::of_name(name).ok_or_else(|| format!("unknown dash {:?}", name))?;
+                let hollow = match words.next() {
+                    Some("hollow") => true,
+                    Some("filled") => false,
+                    other => return Err(format!("couldn't read {:?}", other.unwrap_or(""))),
+                };
+                Ok(Action::STROKE(Stroke {
+                    width,
+                    color,
+                    dash,
+                    hollow,
+                }))
+            }
+            Some("blend") => {
+                let name = words.next().unwrap_or("");
+                let blend =
+                    Blend::of_name(name).ok_or_else(|| format!("unknown blend {:?}", name))?;
+                Ok(Action::BLEND(blend))
+            }
+            Some("set") => {
+                let name = words.next().unwrap_or("");
+                let parameter = Parameter::of_name(name)
+                    .ok_or_else(|| format!("unknown parameter {:?}", name))?;
+                Ok(Action::SET(parameter, parse(words.next())?))
+            }
+            Some("override") => {
+                let index = parse(words.next())?;
+                Ok(Action::OVERRIDE(index, parse_override(&mut words)?))
+            }
+            Some("edit") => {
+                let (target, edit) = parse_edit(&mut words)?;
+                Ok(Action::EDIT(target, edit))
+            }
+            Some("run") => match words.next() {
+                Some("apples") => Ok(Action::RUN(Run::APPLES)),
+                ticks => Ok(Action::RUN(Run::TICKS(parse(ticks)?))),
+            },
+            Some("resize") => Ok(Action::RESIZE(parse(words.next())?, parse(words.next())?)),
+            Some(other) => Err(format!("unknown action {:?}", other)),
+            None => Err(String::from("empty action")),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LoggedAction {
+    pub tick: u64,
+    pub action: Action,
+}
+
+// everything needed to draw a piece again from nothing
+#[derive(Clone, Debug)]
+pub struct Log {
+    pub seed: u64,
+    pub width: f64,
+    pub height: f64,
+    // how long the piece had been running when the log was saved
+    pub ticks: u64,
+    pub actions: Vec<LoggedAction>,
+}
+
+impl Display for Log {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "seed {}", self.seed)?;
+        writeln!(f, "size {} {}", self.width, self.height)?;
+        writeln!(f, "ticks {}", self.ticks)?;
+        for logged in self.actions.iter() {
+            writeln!(f, "{} {}", logged.tick, logged.action)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Log {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut log = Log {
+            seed: 0,
+            width: 0.0,
+            height: 0.0,
+            ticks: 0,
+            actions: vec![],
+        };
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let with_line = |error: String| format!("line {}: {}", number + 1, error);
+            let mut words = line.splitn(2, ' ');
+            match (words.next(), words.next()) {
+                (Some(""), _) => {}
+                (Some("seed"), seed) => log.seed = parse(seed).map_err(with_line)?,
+                (Some("ticks"), ticks) => log.ticks = parse(ticks).map_err(with_line)?,
+                (Some("size"), size) => {
+                    let mut size = size.unwrap_or("").split_whitespace();
+                    log.width = parse(size.next()).map_err(with_line)?;
+                    log.height = parse(size.next()).map_err(with_line)?;
+                }
+                (tick, action) => log.actions.push(LoggedAction {
+                    tick: parse(tick).map_err(with_line)?,
+                    action: action.unwrap_or("").parse().map_err(with_line)?,
+                }),
+            }
+        }
+        Ok(log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Action has no PartialEq, so it is compared by how it is written
+    fn every_action() -> Vec<Action> {
+        vec![
+            Action::CIRCLE(None),
+            Action::CIRCLE(Some(Position::at(1.5, 2.0))),
+            Action::APPLE(None),
+            Action::APPLE(Some(Position::at(3.0, 4.25))),
+            Action::MOVE(2, Position::at(5.0, 6.0)),
+            Action::FREEZE,
+            Action::TRASH,
+            Action::PAUSE,
+            Action::SPEED,
+            Action::COLORMODE,
+            Action::STROKE(Stroke {
+                width: 1.5,
+                color: StrokeColor::PALETTE,
+                dash: Dash::DASHDOT,
+                hollow: true,
+            }),
+            Action::STROKE(Stroke::new()),
+            Action::BLEND(Blend::MULTIPLY),
+            Action::SET(Parameter::SPLIT, 0.004),
+            Action::SET(Parameter::BUDGET, 12.0),
+            Action::OVERRIDE(0, Override::DISTANCE(Some(3.0))),
+            Action::OVERRIDE(1, Override::DISTANCE(None)),
+            Action::OVERRIDE(0, Override::COLOR(Some(7))),
+            Action::OVERRIDE(0, Override::COLOR(None)),
+            Action::OVERRIDE(0, Override::RADIUS(RadiusChange::GROW)),
+            Action::OVERRIDE(0, Override::COLORMODE(ColorMode::HSL)),
+            Action::OVERRIDE(3, Override::RESET),
+            Action::EDIT(Target::CIRCLE(0), Edit::POSITION(Position::at(7.0, 8.0))),
+            Action::EDIT(Target::APPLE(1), Edit::RADIUS(9.5)),
+            Action::EDIT(Target::CIRCLE(2), Edit::COLOR([255, 128, 0])),
+            Action::EDIT(Target::APPLE(0), Edit::STEPS(300)),
+            Action::RUN(Run::TICKS(50)),
+            Action::RUN(Run::APPLES),
+            Action::RESIZE(2480.0, 3508.0),
+        ]
+    }
+
+    #[test]
+    fn actions_read_back_what_they_write() {
+        for action in every_action() {
+            let text = action.to_string();
+            let read: Action = text.parse().unwrap();
+            assert_eq!(read.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn logs_read_back_what_they_write() {
+        let log = Log {
+            seed: 42,
+            width: 640.0,
+            height: 480.0,
+            ticks: 1000,
+            actions: every_action()
+                .into_iter()
+                .enumerate()
+                .map(|(tick, action)| LoggedAction {
+                    tick: tick as u64 * 10,
+                    action,
+                })
+                .collect(),
+        };
+        let text = log.to_string();
+        let read: Log = text.parse().unwrap();
+        assert_eq!(
+            (read.seed, read.width, read.height, read.ticks),
+            (42, 640.0, 480.0, 1000)
+        );
+        assert_eq!(read.actions.len(), log.actions.len());
+        assert_eq!(read.to_string(), text);
+    }
+
+    #[test]
+    fn bad_lines_say_where_they_are() {
+        let error = "seed 1\nsize 10 10\n0 circle\n5 wobble"
+            .parse::<Log>()
+            .unwrap_err();
+        assert!(error.starts_with("line 4:"), "{}", error);
+    }
+}
@@ -0,0 +1,215 @@
+use std::io::Cursor;
+
+// the frames are kept as pngs, a raw frame of a big canvas is megabytes
+pub struct Animation {
+    pub width: u32,
+    pub height: u32,
+    frames: Vec<Vec<u8>>,
+}
+
+impl Animation {
+    pub fn new(width: u32, height: u32) -> Self {
+        Animation {
+            width,
+            height,
+            frames: vec![],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    // rgba, the same size as the animation
+    pub fn push(&mut self, rgba: &[u8]) -> Result<(), String> {
+        if rgba.len() != self.width as usize * self.height as usize * 4 {
+            return Err(String::from("the frame is a different size"));
+        }
+        let mut bytes = vec![];
+        let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_compression(png::Compression::Fast);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(rgba))
+            .map_err(|error| error.to_string())?;
+        self.frames.push(bytes);
+        Ok(())
+    }
+
+    // each frame as a png file
+    pub fn frames(&self) -> &[Vec<u8>] {
+        &self.frames
+    }
+
+    pub fn to_gif(&self, frames_per_second: f64) -> Result<Vec<u8>, String> {
+        if self.width > u16::MAX as u32 || self.height > u16::MAX as u32 {
+            return Err(String::from("too big for a gif"));
+        }
+        // gifs count in hundredths of a second, and browsers slow down anything under 2
+        let delay = u16::max(2, (100.0 / frames_per_second).round() as u16);
+
+        let mut bytes = vec![];
+        {
+            let mut encoder =
+                gif::Encoder::new(&mut bytes, self.width as u16, self.height as u16, &[])
+                    .map_err(|error| error.to_string())?;
+            encoder
+                .set_repeat(gif::Repeat::Infinite)
+                .map_err(|error| error.to_string())?;
+            for png in self.frames.iter() {
+                let mut rgba = decode(png)?;
+                let mut frame = gif::Frame::from_rgba_speed(
+                    self.width as u16,
+                    self.height as u16,
+                    &mut rgba,
+                    10,
+                );
+                frame.delay = delay;
+                encoder
+                    .write_frame(&frame)
+                    .map_err(|error| error.to_string())?;
+            }
+        }
+        Ok(bytes)
+    }
+
+    // every frame in one zip, as NAME-0001.png and on, stored as they are since pngs don't shrink.
+    // written by hand along with its crc32 below, stored entries are all this needs and
+    // not worth a zip crate
+    pub fn to_zip(&self, name: &str) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut directory = vec![];
+        for (index, png) in self.frames.iter().enumerate() {
+            let file_name = format!("{}-{:04}.png", name, index + 1);
+            let offset = bytes.len() as u32;
+            let crc = crc32(png);
+
+            push_u32(&mut bytes, 0x0403_4b50);
+            push_entry(&mut bytes, crc, png.len() as u32, &file_name);
+            push_u16(&mut bytes, 0);
+            bytes.extend_from_slice(file_name.as_bytes());
+            bytes.extend_from_slice(png);
+
+            push_u32(&mut directory, 0x0201_4b50);
+            push_u16(&mut directory, ZIP_VERSION);
+            push_entry(&mut directory, crc, png.len() as u32, &file_name);
+            // no extra field, comment, disk number or attributes
+            for _ in 0..4 {
+                push_u16(&mut directory, 0);
+            }
+            push_u32(&mut directory, 0);
+            push_u32(&mut directory, offset);
+            directory.extend_from_slice(file_name.as_bytes());
+        }
+
+        let directory_offset = bytes.len() as u32;
+        bytes.extend_from_slice(&directory);
+        push_u32(&mut bytes, 0x0605_4b50);
+        push_u16(&mut bytes, 0);
+        push_u16(&mut bytes, 0);
+        push_u16(&mut bytes, self.frames.len() as u16);
+        push_u16(&mut bytes, self.frames.len() as u16);
+        push_u32(&mut bytes, directory.len() as u32);
+        push_u32(&mut bytes, directory_offset);
+        push_u16(&mut bytes, 0);
+        bytes
+    }
+}
+
+// 2.0, the first that knows folders and the lowest anything still reads
+const ZIP_VERSION: u16 = 20;
+// the first of january 1980, the earliest a zip can say
+const ZIP_DATE: u16 = (1 << 5) | 1;
+
+fn push_u16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+// the part a file's local header and its directory entry share, up to the name's length
+fn push_entry(bytes: &mut Vec<u8>, crc: u32, size: u32, file_name: &str) {
+    push_u16(bytes, ZIP_VERSION);
+    // no flags, stored without compression, at midnight
+    push_u16(bytes, 0);
+    push_u16(bytes, 0);
+    push_u16(bytes, 0);
+    push_u16(bytes, ZIP_DATE);
+    push_u32(bytes, crc);
+    push_u32(bytes, size);
+    push_u32(bytes, size);
+    push_u16(bytes, file_name.len() as u16);
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes.iter() {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn decode(png: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = png::Decoder::new(Cursor::new(png))
+        .read_info()
+        .map_err(|error| error.to_string())?;
+    let mut rgba = vec![0; reader.output_buffer_size()];
+    reader
+        .next_frame(&mut rgba)
+        .map_err(|error| error.to_string())?;
+    Ok(rgba)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+    }
+
+    #[test]
+    fn crc_matches_the_standard_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn zips_hold_every_frame_as_it_was() {
+        let mut animation = Animation::new(2, 1);
+        animation.push(&[255, 0, 0, 255, 0, 0, 255, 255]).unwrap();
+        animation.push(&[0; 8]).unwrap();
+        let zip = animation.to_zip("art");
+
+        // the first file starts right after its header and name
+        let first = animation.frames()[0].as_slice();
+        assert_eq!(u32_at(&zip, 0), 0x0403_4b50);
+        assert_eq!(u32_at(&zip, 14), crc32(first));
+        assert_eq!(u32_at(&zip, 18) as usize, first.len());
+        assert_eq!(&zip[30..42], b"art-0001.png");
+        assert_eq!(&zip[42..42 + first.len()], first);
+
+        // the end record says where the directory is and how many files it lists
+        let end = zip.len() - 22;
+        assert_eq!(u32_at(&zip, end), 0x0605_4b50);
+        assert_eq!(zip[end + 10], 2);
+        let directory = u32_at(&zip, end + 16) as usize;
+        assert_eq!(u32_at(&zip, directory), 0x0201_4b50);
+        assert_eq!(directory + u32_at(&zip, end + 12) as usize, end);
+    }
+}
@@ -0,0 +1,114 @@
+// what the circles are drawn over, the same on the page and from the command line
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Fill {
+    SOLID,
+    LINEAR,
+    RADIAL,
+}
+
+pub const ALL_FILLS: [Fill; 3] = [Fill::SOLID, Fill::LINEAR, Fill::RADIAL];
+
+impl Fill {
+    pub fn name(self) -> &'static str {
+        match self {
+            Fill::SOLID => "solid",
+            Fill::LINEAR => "linear",
+            Fill::RADIAL => "radial",
+        }
+    }
+
+    pub fn of_name(name: &str) -> Option<Fill> {
+        ALL_FILLS.iter().copied().find(|fill| fill.name() == name)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Background {
+    pub fill: Fill,
+    pub color: [u8; 3],
+    // where a gradient ends up, at the bottom or the edge
+    pub to_color: [u8; 3],
+    // how much paper texture, from 0 for none to 1
+    pub grain: f64,
+}
+
+impl Background {
+    pub fn white() -> Self {
+        Background {
+            fill: Fill::SOLID,
+            color: [255, 255, 255],
+            to_color: [255, 255, 255],
+            grain: 0.0,
+        }
+    }
+
+    pub fn color_at(&self, x: u32, y: u32, width: u32, height: u32) -> [u8; 3] {
+        let t = match self.fill {
+            Fill::SOLID => 0.0,
+            Fill::LINEAR => y as f64 / f64::max(1.0, height as f64 - 1.0),
+            Fill::RADIAL => {
+                let (cx, cy) = (width as f64 / 2.0, height as f64 / 2.0);
+                f64::hypot(x as f64 + 0.5 - cx, y as f64 + 0.5 - cy) / f64::hypot(cx, cy)
+            }
+        };
+        let shade = 1.0 - self.grain * 0.2 * paper(x, y);
+        let mut rgb = [0; 3];
+        for (channel, value) in rgb.iter_mut().enumerate() {
+            let mixed = self.color[channel] as f64 * (1.0 - t) + self.to_color[channel] as f64 * t;
+            *value = (mixed * shade).round() as u8;
+        }
+        rgb
+    }
+
+    // rgba, opaque
+    pub fn pixels(&self, width: u32, height: u32) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            for x in 0..width {
+                pixels.extend_from_slice(&self.color_at(x, y, width, height));
+                pixels.push(255);
+            }
+        }
+        pixels
+    }
+}
+
+// between 0 and 1, the same for the same x and y every time
+fn hash(x: i64, y: i64) -> f64 {
+    let mut h = (x.wrapping_mul(374_761_393) ^ y.wrapping_mul(668_265_263)) as u64;
+    h = (h ^ (h >> 13)).wrapping_mul(1_274_126_177);
+    h ^= h >> 16;
+    (h & 0xffff) as f64 / 65536.0
+}
+
+// smooth noise with features about `width` by `height` pixels
+fn value_noise(x: u32, y: u32, width: f64, height: f64) -> f64 {
+    let (fx, fy) = (x as f64 / width, y as f64 / height);
+    let (x0, y0) = (fx.floor(), fy.floor());
+    let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smooth(fx - x0), smooth(fy - y0));
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let top = hash(x0, y0) * (1.0 - tx) + hash(x0 + 1, y0) * tx;
+    let bottom = hash(x0, y0 + 1) * (1.0 - tx) + hash(x0 + 1, y0 + 1) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+// fine speckle over blotches over long soft fibres, darker is closer to 1
+fn paper(x: u32, y: u32) -> f64 {
+    0.4 * hash(x as i64, y as i64)
+        + 0.35 * value_noise(x, y, 6.0, 6.0)
+        + 0.25 * value_noise(x, y, 40.0, 5.0)
+}
+
+pub fn parse_color(text: &str) -> Option<[u8; 3]> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+pub fn to_hex(rgb: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
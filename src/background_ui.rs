@@ -0,0 +1,109 @@
+use std::cell::RefCell;
+use wasm_bindgen::{Clamped, JsCast};
+
+use crate::background::{self, Fill};
+use crate::circle::Universe;
+use crate::{body, context, document};
+
+const BACKGROUND_CANVAS_ID: &str = "background-canvas";
+
+struct Background {
+    background: background::Background,
+    // saved images and recordings leave the background out
+    transparent_export: bool,
+}
+
+thread_local! {
+    static BACKGROUND: RefCell<Background> = RefCell::new(Background {
+        background: background::Background::white(),
+        transparent_export: false,
+    });
+}
+
+// under all the layers
+pub fn canvas() -> web_sys::HtmlCanvasElement {
+    if let Some(element) = document().get_element_by_id(BACKGROUND_CANVAS_ID) {
+        return element.dyn_into::<web_sys::HtmlCanvasElement>().unwrap();
+    }
+    let canvas = document()
+        .create_element("canvas")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+    canvas.set_id(BACKGROUND_CANVAS_ID);
+    body()
+        .insert_before(&canvas, body().first_child().as_ref())
+        .unwrap();
+    canvas
+}
+
+// call after the background or the canvas size changes
+pub fn draw() {
+    let canvas = canvas();
+    let (width, height) = (canvas.width(), canvas.height());
+    if width == 0 || height == 0 {
+        return;
+    }
+    let pixels = BACKGROUND.with(|background| background.borrow().background.pixels(width, height));
+    let image =
+        web_sys::ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixels), width, height)
+            .unwrap();
+    context(&canvas).put_image_data(&image, 0.0, 0.0).unwrap();
+}
+
+// fills a canvas that is about to be exported, unless it should be transparent
+pub fn paint(target: &web_sys::HtmlCanvasElement) {
+    if BACKGROUND.with(|background| background.borrow().transparent_export) {
+        return;
+    }
+    context(target)
+        .draw_image_with_html_canvas_element(&canvas(), 0.0, 0.0)
+        .unwrap();
+}
+
+fn update(f: impl FnOnce(&mut Background)) {
+    BACKGROUND.with(|background| f(&mut background.borrow_mut()));
+    draw();
+}
+
+pub fn fill(_universe: &Universe) -> &'static str {
+    BACKGROUND.with(|background| background.borrow().background.fill.name())
+}
+
+pub fn set_fill(_universe: &mut Universe, name: &str) {
+    if let Some(fill) = Fill::of_name(name) {
+        update(|background| background.background.fill = fill);
+    }
+}
+
+pub fn color(_universe: &Universe) -> [u8; 3] {
+    BACKGROUND.with(|background| background.borrow().background.color)
+}
+
+pub fn set_color(_universe: &mut Universe, rgb: [u8; 3]) {
+    update(|background| background.background.color = rgb);
+}
+
+pub fn to_color(_universe: &Universe) -> [u8; 3] {
+    BACKGROUND.with(|background| background.borrow().background.to_color)
+}
+
+pub fn set_to_color(_universe: &mut Universe, rgb: [u8; 3]) {
+    update(|background| background.background.to_color = rgb);
+}
+
+pub fn grain(_universe: &Universe) -> f64 {
+    BACKGROUND.with(|background| background.borrow().background.grain)
+}
+
+pub fn set_grain(_universe: &mut Universe, value: f64) {
+    update(|background| background.background.grain = value);
+}
+
+pub fn transparent_export(_universe: &Universe) -> bool {
+    BACKGROUND.with(|background| background.borrow().transparent_export)
+}
+
+pub fn set_transparent_export(_universe: &mut Universe, value: bool) {
+    BACKGROUND.with(|background| background.borrow_mut().transparent_export = value);
+}
@@ -0,0 +1,156 @@
+// how a drawing is mixed with what is already underneath it
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Blend {
+    NORMAL,
+    MULTIPLY,
+    SCREEN,
+    OVERLAY,
+    DIFFERENCE,
+    LIGHTER,
+}
+
+pub const ALL_BLENDS: [Blend; 6] = [
+    Blend::NORMAL,
+    Blend::MULTIPLY,
+    Blend::SCREEN,
+    Blend::OVERLAY,
+    Blend::DIFFERENCE,
+    Blend::LIGHTER,
+];
+
+impl Blend {
+    pub fn name(self) -> &'static str {
+        match self {
+            Blend::NORMAL => "normal",
+            Blend::MULTIPLY => "multiply",
+            Blend::SCREEN => "screen",
+            Blend::OVERLAY => "overlay",
+            Blend::DIFFERENCE => "difference",
+            Blend::LIGHTER => "lighter",
+        }
+    }
+
+    pub fn of_name(name: &str) -> Option<Blend> {
+        ALL_BLENDS
+            .iter()
+            .copied()
+            .find(|blend| blend.name() == name)
+    }
+
+    // the canvas globalCompositeOperation
+    pub fn composite_operation(self) -> &'static str {
+        match self {
+            Blend::NORMAL => "source-over",
+            blend => blend.name(),
+        }
+    }
+
+    // the css mix-blend-mode
+    pub fn mix_blend_mode(self) -> &'static str {
+        match self {
+            Blend::LIGHTER => "plus-lighter",
+            blend => blend.name(),
+        }
+    }
+
+    // one channel of an opaque color drawn over another, both between 0 and 1
+    fn mix(self, below: f64, above: f64) -> f64 {
+        match self {
+            Blend::NORMAL => above,
+            Blend::MULTIPLY => below * above,
+            Blend::SCREEN => below + above - below * above,
+            Blend::OVERLAY if below <= 0.5 => 2.0 * below * above,
+            Blend::OVERLAY => 1.0 - 2.0 * (1.0 - below) * (1.0 - above),
+            Blend::DIFFERENCE => (below - above).abs(),
+            Blend::LIGHTER => f64::min(1.0, below + above),
+        }
+    }
+
+    // rgba between 0 and 1, not premultiplied, the same as a canvas does it
+    pub fn composite(self, below: [f64; 4], above: [f64; 4]) -> [f64; 4] {
+        let (below_alpha, above_alpha) = (below[3], above[3]);
+        let alpha = match self {
+            Blend::LIGHTER => f64::min(1.0, above_alpha + below_alpha),
+            _ => above_alpha + below_alpha * (1.0 - above_alpha),
+        };
+        if alpha == 0.0 {
+            return [0.0; 4];
+        }
+        let mut result = [0.0, 0.0, 0.0, alpha];
+        for (channel, value) in result.iter_mut().enumerate().take(3) {
+            let premultiplied = match self {
+                // lighter adds, rather than mixing and then drawing source-over
+                Blend::LIGHTER => f64::min(
+                    1.0,
+                    above[channel] * above_alpha + below[channel] * below_alpha,
+                ),
+                _ => {
+                    above_alpha * (1.0 - below_alpha) * above[channel]
+                        + above_alpha * below_alpha * self.mix(below[channel], above[channel])
+                        + (1.0 - above_alpha) * below_alpha * below[channel]
+                }
+            };
+            *value = premultiplied / alpha;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: [f64; 4], expected: [f64; 4]) {
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-9, "{:?} isn't {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn opaque_colors_mix_by_the_blend() {
+        let below = [0.75, 0.5, 0.0, 1.0];
+        let above = [0.25, 0.5, 1.0, 1.0];
+        assert_close(Blend::NORMAL.composite(below, above), above);
+        assert_close(
+            Blend::MULTIPLY.composite(below, above),
+            [0.1875, 0.25, 0.0, 1.0],
+        );
+        assert_close(
+            Blend::SCREEN.composite(below, above),
+            [0.8125, 0.75, 1.0, 1.0],
+        );
+        assert_close(
+            Blend::OVERLAY.composite(below, above),
+            [0.625, 0.5, 0.0, 1.0],
+        );
+        assert_close(
+            Blend::DIFFERENCE.composite(below, above),
+            [0.5, 0.0, 1.0, 1.0],
+        );
+        assert_close(Blend::LIGHTER.composite(below, above), [1.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn nothing_drawn_leaves_what_was_there() {
+        let below = [0.2, 0.4, 0.6, 0.8];
+        for blend in ALL_BLENDS.iter() {
+            assert_close(blend.composite(below, [1.0, 1.0, 1.0, 0.0]), below);
+        }
+        for blend in ALL_BLENDS.iter() {
+            assert_close(blend.composite([0.0; 4], [0.0; 4]), [0.0; 4]);
+        }
+    }
+
+    #[test]
+    fn half_see_through_goes_halfway() {
+        assert_close(
+            Blend::NORMAL.composite([0.0, 0.0, 1.0, 1.0], [1.0, 0.0, 0.0, 0.5]),
+            [0.5, 0.0, 0.5, 1.0],
+        );
+        // over nothing, the color stays and only the alpha says how much
+        assert_close(
+            Blend::MULTIPLY.composite([0.0; 4], [1.0, 0.5, 0.0, 0.5]),
+            [1.0, 0.5, 0.0, 0.5],
+        );
+    }
+}
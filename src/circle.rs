@@ -1,11 +1,19 @@
-use js_sys::Math::random as js_math_random;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
 use std::f64;
 use std::fmt::{self, Display};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 
+use crate::action::{Action, Edit, Log, LoggedAction, Override, Run, Target};
+use crate::blend::Blend;
+use crate::stroke::Stroke;
 
 const MIN_POS: f64 = 0.0;
+const MAX_RADIUS: f64 = 200.0;
+// the most ticks a frame runs to finish the apples
+const MAX_APPLE_STEPS: u32 = 5000;
 
 #[derive(Debug, Clone)]
 pub struct Position {
@@ -13,34 +21,41 @@ pub struct Position {
     pub y: f64,
 }
 
-// not sure what I'm going to do with this but currently leaving in option to use either
-fn random() -> f64 {
-    if false {
-        js_math_random()
-    } else {
-        rand::random()
-    }
+fn random(rng: &mut StdRng) -> f64 {
+    rng.gen()
 }
 
-fn random_in_range(min: f64, max: f64) -> f64 {
-    (random() * (max - min)) + min
+fn random_in_range(rng: &mut StdRng, min: f64, max: f64) -> f64 {
+    (random(rng) * (max - min)) + min
 }
 
-fn saturating_random_in_range(current: f64, delta: f64, min: f64, max: f64) -> f64 {
+fn saturating_random_in_range(
+    rng: &mut StdRng,
+    current: f64,
+    (delta, min, max): (f64, f64, f64),
+) -> f64 {
     let min = f64::max(min, current - delta);
     let max = f64::min(max, current + delta);
-    random_in_range(min, max)
+    random_in_range(rng, min, max)
 }
 
 impl Position {
-    fn new(config: &CircleConfig) -> Self {
+    fn new(config: &CircleConfig, rng: &mut StdRng) -> Self {
         Position {
-            x: random_in_range(MIN_POS, config.width),
-            y: random_in_range(MIN_POS, config.height),
+            x: random_in_range(rng, MIN_POS, config.width),
+            y: random_in_range(rng, MIN_POS, config.height),
         }
     }
 
-    fn update(&mut self, config: &CircleConfig, _radius: f64) {
+    pub fn at(x: f64, y: f64) -> Self {
+        Position { x, y }
+    }
+
+    fn distance(&self, other: &Position) -> f64 {
+        f64::hypot(self.x - other.x, self.y - other.y)
+    }
+
+    fn update(&mut self, config: &CircleConfig, _radius: f64, rng: &mut StdRng) {
         // let max_position_delta = (100.0 - radius) * config.max_position_delta.powi(2)
         //     + (2. * radius - 100.0) * config.max_position_delta;
         // let max_position_delta = (2. * radius).powf(config.max_position_delta);
@@ -50,8 +65,8 @@ impl Position {
 
         let y_min = f64::max(MIN_POS, self.y - max_position_delta);
         let y_max = f64::min(config.height, self.y + max_position_delta);
-        self.x = random_in_range(x_min, x_max);
-        self.y = random_in_range(y_min, y_max);
+        self.x = random_in_range(rng, x_min, x_max);
+        self.y = random_in_range(rng, y_min, y_max);
     }
 }
 
@@ -65,15 +80,15 @@ impl Display for ColorBit {
 }
 
 impl ColorBit {
-    fn rand() -> Self {
-        ColorBit(f64::round(random() * 255 as f64) as u8)
+    fn rand(rng: &mut StdRng) -> Self {
+        ColorBit(f64::round(random(rng) * 255 as f64) as u8)
     }
 
-    fn update(&mut self, config: &CircleConfig) -> () {
+    fn update(&mut self, config: &CircleConfig, rng: &mut StdRng) -> () {
         let min = self.0.saturating_sub(config.max_color_delta);
         let max = self.0.saturating_add(config.max_color_delta);
 
-        self.0 = f64::floor(random() * ((max - min).saturating_add(1)) as f64) as u8 + min;
+        self.0 = f64::floor(random(rng) * ((max - min).saturating_add(1)) as f64) as u8 + min;
     }
 }
 
@@ -87,12 +102,12 @@ impl Display for Opacity {
 }
 
 impl Opacity {
-    fn rand() -> Self {
-        Opacity(random())
+    fn rand(rng: &mut StdRng) -> Self {
+        Opacity(random(rng))
     }
 
-    fn update(&mut self) -> () {
-        self.0 = random()
+    fn update(&mut self, rng: &mut StdRng) -> () {
+        self.0 = random(rng)
     }
 }
 
@@ -105,12 +120,12 @@ pub struct RGBColor {
 }
 
 impl RGBColor {
-    fn new() -> Self {
+    fn new(rng: &mut StdRng) -> Self {
         RGBColor {
-            r: ColorBit::rand(),
-            g: ColorBit::rand(),
-            b: ColorBit::rand(),
-            a: Opacity::rand(),
+            r: ColorBit::rand(rng),
+            g: ColorBit::rand(rng),
+            b: ColorBit::rand(rng),
+            a: Opacity::rand(rng),
         }
     }
 
@@ -118,11 +133,11 @@ impl RGBColor {
         format!("rgb({}, {}, {}, {})", self.r, self.g, self.b, self.a)
     }
 
-    fn update(&mut self, config: &CircleConfig) {
-        self.r.update(&config);
-        self.g.update(&config);
-        self.b.update(&config);
-        self.a.update();
+    fn update(&mut self, config: &CircleConfig, rng: &mut StdRng) {
+        self.r.update(&config, rng);
+        self.g.update(&config, rng);
+        self.b.update(&config, rng);
+        self.a.update(rng);
     }
 
     pub fn to_slightly_darker_color(&self) -> String {
@@ -136,12 +151,12 @@ impl RGBColor {
 pub struct Hue(f64);
 
 impl Hue {
-    pub fn new() -> Self {
-        Hue(random_in_range(0.0, 360.0))
+    pub fn new(rng: &mut StdRng) -> Self {
+        Hue(random_in_range(rng, 0.0, 360.0))
     }
-    pub fn update(&mut self, config: &CircleConfig) {
+    pub fn update(&mut self, config: &CircleConfig, rng: &mut StdRng) {
         let max_color_delta = config.max_color_delta as f64;
-        self.0 = random_in_range(self.0 - max_color_delta, self.0 + max_color_delta) % 360.0
+        self.0 = random_in_range(rng, self.0 - max_color_delta, self.0 + max_color_delta) % 360.0
     }
 }
 #[derive(Debug, Clone, Copy)]
@@ -153,21 +168,21 @@ pub struct HSL {
 }
 
 impl HSL {
-    pub fn new() -> Self {
+    pub fn new(rng: &mut StdRng) -> Self {
         HSL {
-            hue: Hue::new(),
-            saturation: random_in_range(0.5, 1.0),
-            lightness: random_in_range(0.25, 0.75),
-            opacity: Opacity::rand(),
+            hue: Hue::new(rng),
+            saturation: random_in_range(rng, 0.5, 1.0),
+            lightness: random_in_range(rng, 0.25, 0.75),
+            opacity: Opacity::rand(rng),
         }
     }
 
-    pub fn update(&mut self, config: &CircleConfig) {
-        self.hue.update(&config);
-        self.opacity.update();
+    pub fn update(&mut self, config: &CircleConfig, rng: &mut StdRng) {
+        self.hue.update(&config, rng);
+        self.opacity.update(rng);
         let delta = config.max_color_delta as f64 / 360. * 1.5;
-        self.saturation = saturating_random_in_range(self.saturation, delta, 0.4, 1.0);
-        self.lightness = saturating_random_in_range(self.lightness, delta, 0.1, 0.9);
+        self.saturation = saturating_random_in_range(rng, self.saturation, (delta, 0.4, 1.0));
+        self.lightness = saturating_random_in_range(rng, self.lightness, (delta, 0.1, 0.9));
     }
 
     pub fn to_hsl(&self) -> String {
@@ -188,6 +203,16 @@ impl HSL {
         )
     }
 
+    fn to_rgb(self) -> [u8; 3] {
+        let (r, g, b) = ::hsl::HSL {
+            h: self.hue.0.rem_euclid(360.0),
+            s: self.saturation,
+            l: self.lightness,
+        }
+        .to_rgb();
+        [r, g, b]
+    }
+
     pub fn to_slightly_darker_color(self) -> Self {
         Self {
             lightness: f64::max(0.0, self.lightness - 0.1),
@@ -195,6 +220,20 @@ impl HSL {
         }
     }
 
+    fn to_slightly_lighter_color(self) -> Self {
+        Self {
+            lightness: f64::min(1.0, self.lightness + 0.1),
+            ..self
+        }
+    }
+
+    fn to_complementary_color(self) -> Self {
+        Self {
+            hue: Hue((self.hue.0 + 180.0) % 360.0),
+            ..self
+        }
+    }
+
     fn from_rgb(rgb: &RGBColor) -> HSL {
         let r = rgb.r.0 as f64 / 255.0;
         let g = rgb.g.0 as f64 / 255.0;
@@ -246,17 +285,17 @@ impl Color {
         hsl.to_slightly_darker_color().to_hsl()
     }
 
-    pub fn new(color_mode: &ColorMode) -> Self {
+    pub fn new(color_mode: &ColorMode, rng: &mut StdRng) -> Self {
         match color_mode {
-            ColorMode::RGB => Color::RGB(RGBColor::new()),
-            ColorMode::HSL => Color::HSL(HSL::new()),
+            ColorMode::RGB => Color::RGB(RGBColor::new(rng)),
+            ColorMode::HSL => Color::HSL(HSL::new(rng)),
         }
     }
 
-    pub fn update(&mut self, config: &CircleConfig) {
+    pub fn update(&mut self, config: &CircleConfig, rng: &mut StdRng) {
         match self {
-            Color::RGB(rgb) => rgb.update(&config),
-            Color::HSL(hsl) => hsl.update(&config),
+            Color::RGB(rgb) => rgb.update(&config, rng),
+            Color::HSL(hsl) => hsl.update(&config, rng),
         }
     }
 
@@ -267,26 +306,244 @@ impl Color {
             Color::HSL(hsl) => hsl.to_hsla(),
         }
     }
+
+    // the same colors as the css strings, for drawing without a browser
+    pub fn to_rgb(&self) -> [u8; 3] {
+        match self {
+            Color::RGB(rgb) => [rgb.r.0, rgb.g.0, rgb.b.0],
+            Color::HSL(hsl) => hsl.to_rgb(),
+        }
+    }
+
+    pub fn opacity(&self) -> f64 {
+        match self {
+            Color::RGB(rgb) => rgb.a.0,
+            Color::HSL(hsl) => hsl.opacity.0,
+        }
+    }
+
+    pub fn to_slightly_darker_rgb(&self) -> [u8; 3] {
+        let hsl = match self {
+            Color::HSL(hsl) => *hsl,
+            Color::RGB(rgb) => HSL::from_rgb(rgb),
+        };
+        hsl.to_slightly_darker_color().to_rgb()
+    }
+
+    pub fn to_slightly_lighter_rgb(&self) -> [u8; 3] {
+        let hsl = match self {
+            Color::HSL(hsl) => *hsl,
+            Color::RGB(rgb) => HSL::from_rgb(rgb),
+        };
+        hsl.to_slightly_lighter_color().to_rgb()
+    }
+
+    // the same color, changing from now on the way the mode changes colors
+    pub fn to_mode(&self, color_mode: &ColorMode) -> Self {
+        match (self, color_mode) {
+            (Color::RGB(rgb), ColorMode::HSL) => {
+                let hsl = ::hsl::HSL::from_rgb(&[rgb.r.0, rgb.g.0, rgb.b.0]);
+                Color::HSL(HSL {
+                    hue: Hue(hsl.h),
+                    saturation: hsl.s,
+                    lightness: hsl.l,
+                    opacity: rgb.a,
+                })
+            }
+            (Color::HSL(hsl), ColorMode::RGB) => {
+                let [r, g, b] = hsl.to_rgb();
+                Color::RGB(RGBColor {
+                    r: ColorBit(r),
+                    g: ColorBit(g),
+                    b: ColorBit(b),
+                    a: hsl.opacity,
+                })
+            }
+            (color, _) => color.clone(),
+        }
+    }
+
+    // keeps the mode and the opacity
+    pub fn set_rgb(&mut self, rgb: [u8; 3]) {
+        *self = match self {
+            Color::RGB(color) => Color::RGB(RGBColor {
+                r: ColorBit(rgb[0]),
+                g: ColorBit(rgb[1]),
+                b: ColorBit(rgb[2]),
+                a: color.a,
+            }),
+            Color::HSL(color) => {
+                let hsl = ::hsl::HSL::from_rgb(&rgb);
+                Color::HSL(HSL {
+                    hue: Hue(hsl.h),
+                    saturation: hsl.s,
+                    lightness: hsl.l,
+                    opacity: color.opacity,
+                })
+            }
+        }
+    }
+
+    // hue in degrees, saturation and lightness between 0 and 1
+    pub fn hsl_values(&self) -> (f64, f64, f64) {
+        match self {
+            Color::HSL(hsl) => (hsl.hue.0.rem_euclid(360.0), hsl.saturation, hsl.lightness),
+            Color::RGB(_) => {
+                let hsl = ::hsl::HSL::from_rgb(&self.to_rgb());
+                (hsl.h, hsl.s, hsl.l)
+            }
+        }
+    }
+
+    pub fn mode(&self) -> ColorMode {
+        match self {
+            Color::RGB(_) => ColorMode::RGB,
+            Color::HSL(_) => ColorMode::HSL,
+        }
+    }
+
+    pub fn to_complementary_rgb(&self) -> [u8; 3] {
+        let hsl = match self {
+            Color::HSL(hsl) => *hsl,
+            Color::RGB(rgb) => HSL::from_rgb(rgb),
+        };
+        hsl.to_complementary_color().to_rgb()
+    }
+}
+// how a circle's size changes as it moves
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RadiusChange {
+    STEADY,
+    GROW,
+    SHRINK,
+}
+
+const ALL_RADIUS_CHANGES: [RadiusChange; 3] = [
+    RadiusChange::STEADY,
+    RadiusChange::GROW,
+    RadiusChange::SHRINK,
+];
+
+impl RadiusChange {
+    pub fn name(self) -> &'static str {
+        match self {
+            RadiusChange::STEADY => "steady",
+            RadiusChange::GROW => "grow",
+            RadiusChange::SHRINK => "shrink",
+        }
+    }
+
+    pub fn of_name(name: &str) -> Option<RadiusChange> {
+        ALL_RADIUS_CHANGES
+            .iter()
+            .copied()
+            .find(|change| change.name() == name)
+    }
+
+    pub fn next(self) -> RadiusChange {
+        match self {
+            RadiusChange::STEADY => RadiusChange::GROW,
+            RadiusChange::GROW => RadiusChange::SHRINK,
+            RadiusChange::SHRINK => RadiusChange::STEADY,
+        }
+    }
+
+    fn display(self) -> String {
+        String::from(match self {
+            RadiusChange::STEADY => "⏺️",
+            RadiusChange::GROW => "🎈",
+            RadiusChange::SHRINK => "🤏",
+        })
+    }
+
+    pub fn to_button_display(self) -> String {
+        self.next().display()
+    }
+
+    // a tenth of a percent a tick, between one pixel and MAX_RADIUS
+    fn apply(self, radius: f64) -> f64 {
+        match self {
+            RadiusChange::STEADY => radius,
+            RadiusChange::GROW => f64::min(MAX_RADIUS, radius * 1.001),
+            RadiusChange::SHRINK => f64::max(1.0, radius * 0.999),
+        }
+    }
+}
+
+// what one circle does differently from the rest, none means the same as the universe
+#[derive(Clone, Debug, PartialEq)]
+pub struct Overrides {
+    pub max_position_delta: Option<f64>,
+    pub max_color_delta: Option<u8>,
+    pub radius_change: RadiusChange,
 }
+
+impl Overrides {
+    pub fn new() -> Self {
+        Overrides {
+            max_position_delta: None,
+            max_color_delta: None,
+            radius_change: RadiusChange::STEADY,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Overrides::new()
+    }
+
+    fn apply(&self, config: &CircleConfig) -> CircleConfig {
+        CircleConfig {
+            max_position_delta: self.max_position_delta.unwrap_or(config.max_position_delta),
+            max_color_delta: self.max_color_delta.unwrap_or(config.max_color_delta),
+            ..config.clone()
+        }
+    }
+}
+
+impl Default for Overrides {
+    fn default() -> Self {
+        Overrides::new()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Circle {
+    // stays the same while others come and go, unlike its index
+    pub id: u64,
     pub position: Position,
+    // where it was before the last tick
+    pub previous: Position,
     pub color: Color,
     pub radius: f64,
+    pub overrides: Overrides,
+    // ticks it has moved for
+    pub age: u64,
 }
 
 impl Circle {
-    pub fn new(config: &Config, circle_config: &CircleConfig) -> Self {
+    pub fn new_at(config: &Config, id: u64, position: Position, rng: &mut StdRng) -> Self {
         Circle {
-            position: Position::new(&circle_config),
-            color: Color::new(&config.color_mode),
+            id,
+            previous: position.clone(),
+            position,
+            color: Color::new(&config.color_mode, rng),
             radius: config.radius,
+            overrides: Overrides::new(),
+            age: 0,
         }
     }
 
-    pub fn update(&mut self, config: &CircleConfig) {
-        self.position.update(&config, self.radius);
-        self.color.update(&config);
+    pub fn contains(&self, position: &Position) -> bool {
+        self.position.distance(position) <= self.radius
+    }
+
+    pub fn update(&mut self, config: &CircleConfig, rng: &mut StdRng) {
+        let config = &self.overrides.apply(config);
+        self.previous = self.position.clone();
+        self.position.update(&config, self.radius, rng);
+        self.color.update(&config, rng);
+        self.radius = self.overrides.radius_change.apply(self.radius);
+        self.age += 1;
     }
 
     pub fn color(&self) -> String {
@@ -300,39 +557,290 @@ pub struct Universe {
     pub circle_config: CircleConfig,
     pub circles: Vec<Circle>,
     pub apples: Vec<Apple>,
+    pub seed: u64,
+    pub ticks: u64,
+    pub log: Vec<LoggedAction>,
+    // actions from a log being replayed, waiting for their tick
+    pending: VecDeque<LoggedAction>,
+    clear_requested: bool,
+    resize_requested: bool,
+    // when to pause again after a run
+    stop: Option<Stop>,
+    // seeded, and only used by this universe, so replaying its log draws the same numbers
+    rng: StdRng,
+    next_id: u64,
+}
+
+#[derive(Clone, Copy)]
+enum Stop {
+    AT(u64),
+    APPLES,
 }
 
 impl Universe {
+    pub fn new(width: f64, height: f64, seed: u64) -> Self {
+        Universe {
+            config: Config {
+                status: Status::RUNNING,
+                speed: Speed::new(),
+                stroke: Stroke::new(),
+                radius: 10.,
+                apple_steps: 1000,
+                apple_split_chance: 0.0,
+                apple_children: 2,
+                max_apples: 20,
+                initial_height: height,
+                initial_width: width,
+                color_mode: ColorMode::RGB,
+                blend: Blend::NORMAL,
+            },
+            circle_config: CircleConfig {
+                height,
+                width,
+                max_position_delta: 6.3,
+                max_color_delta: 5,
+            },
+            circles: vec![],
+            apples: vec![],
+            seed,
+            ticks: 0,
+            log: vec![],
+            pending: VecDeque::new(),
+            clear_requested: false,
+            resize_requested: false,
+            stop: None,
+            rng: StdRng::seed_from_u64(seed),
+            next_id: 0,
+        }
+    }
+
+    // a fresh universe that will redo everything in the log, tick for tick
+    pub fn replay(log: &Log) -> Self {
+        let mut universe = Universe::new(log.width, log.height, log.seed);
+        universe.pending = log.actions.iter().cloned().collect();
+        universe
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub fn to_log(&self) -> Log {
+        Log {
+            seed: self.seed,
+            width: self.config.initial_width,
+            height: self.config.initial_height,
+            ticks: self.ticks,
+            actions: self.log.clone(),
+        }
+    }
+
+    // every change a user makes goes through here so it can be replayed
+    pub fn perform(&mut self, action: Action) {
+        self.log.push(LoggedAction {
+            tick: self.ticks,
+            action: action.clone(),
+        });
+        action.apply(self);
+    }
+
+    pub fn request_clear(&mut self) {
+        self.clear_requested = true;
+    }
+
+    // true once after the board has been wiped, so whoever is drawing can wipe the picture
+    pub fn take_clear_request(&mut self) -> bool {
+        std::mem::replace(&mut self.clear_requested, false)
+    }
+
     pub fn tick(&mut self) {
-        self.apples.drain_filter(|apple| Apple::update(apple));
+        while self
+            .pending
+            .front()
+            .map_or(false, |logged| logged.tick <= self.ticks)
+        {
+            let logged = self.pending.pop_front().unwrap();
+            self.perform(logged.action);
+        }
+
+        let rng = &mut self.rng;
+        self.apples.drain_filter(|apple| apple.update(rng));
+        self.split_apples();
 
         match self.config.status {
             Status::RUNNING => {
                 for circle in self.circles.iter_mut() {
-                    circle.update(&self.circle_config)
+                    circle.update(&self.circle_config, &mut self.rng)
                 }
             }
 
             Status::PAUSED => {}
         }
+
+        self.ticks += 1;
+
+        let stopped = match self.stop {
+            Some(Stop::AT(tick)) => self.ticks >= tick,
+            Some(Stop::APPLES) => self.apples.is_empty(),
+            None => false,
+        };
+        if stopped {
+            self.stop = None;
+            self.config.status = Status::PAUSED;
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.config.status.toggle();
+        self.stop = None;
+    }
+
+    pub fn run(&mut self, run: Run) {
+        self.config.status = Status::RUNNING;
+        self.stop = Some(match run {
+            Run::TICKS(ticks) => Stop::AT(self.ticks + ticks),
+            Run::APPLES => Stop::APPLES,
+        });
     }
 
     pub fn is_empty(&self) -> bool {
         self.circles.is_empty() && self.apples.is_empty()
     }
+    fn new_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
     pub fn add_circle(&mut self) {
+        let position = Position::new(&self.circle_config, &mut self.rng);
+        self.add_circle_at(position)
+    }
+
+    pub fn add_circle_at(&mut self, position: Position) {
+        let id = self.new_id();
         self.circles
-            .push(Circle::new(&self.config, &self.circle_config))
+            .push(Circle::new_at(&self.config, id, position, &mut self.rng))
     }
 
     pub fn add_apple(&mut self) {
+        let position = Position::new(&self.circle_config, &mut self.rng);
+        self.add_apple_at(position)
+    }
+
+    pub fn add_apple_at(&mut self, position: Position) {
+        let id = self.new_id();
         self.apples.push(Apple {
-            circle: Circle::new(&self.config, &self.circle_config),
+            circle: Circle::new_at(&self.config, id, position, &mut self.rng),
             config: self.circle_config.clone(),
             steps: self.config.apple_steps,
         })
     }
 
+    pub fn circle_index(&self, id: u64) -> Option<usize> {
+        self.circles.iter().position(|circle| circle.id == id)
+    }
+
+    pub fn apple_index(&self, id: u64) -> Option<usize> {
+        self.apples.iter().position(|apple| apple.circle.id == id)
+    }
+
+    // circles are drawn in order, so the last one under the point is the one on top
+    pub fn circle_at(&self, position: &Position) -> Option<usize> {
+        self.circles
+            .iter()
+            .rposition(|circle| circle.contains(position))
+    }
+
+    pub fn apple_at(&self, position: &Position) -> Option<usize> {
+        self.apples
+            .iter()
+            .rposition(|apple| apple.circle.contains(position))
+    }
+
+    fn on_canvas(&self, position: Position) -> Position {
+        Position::at(
+            f64::min(f64::max(MIN_POS, position.x), self.circle_config.width),
+            f64::min(f64::max(MIN_POS, position.y), self.circle_config.height),
+        )
+    }
+
+    pub fn move_circle(&mut self, index: usize, position: Position) {
+        let position = self.on_canvas(position);
+        if let Some(circle) = self.circles.get_mut(index) {
+            circle.position = position;
+        }
+    }
+
+    pub fn edit(&mut self, target: Target, edit: Edit) {
+        let edit = match edit {
+            Edit::POSITION(position) => Edit::POSITION(self.on_canvas(position)),
+            edit => edit,
+        };
+        let (circle, steps) = match target {
+            Target::CIRCLE(index) => (self.circles.get_mut(index), None),
+            Target::APPLE(index) => match self.apples.get_mut(index) {
+                Some(apple) => (Some(&mut apple.circle), Some(&mut apple.steps)),
+                None => (None, None),
+            },
+        };
+        let circle = match circle {
+            Some(circle) => circle,
+            None => return,
+        };
+        match edit {
+            Edit::POSITION(position) => circle.position = position,
+            Edit::RADIUS(radius) => circle.radius = f64::min(MAX_RADIUS, f64::max(1.0, radius)),
+            Edit::COLOR(rgb) => circle.color.set_rgb(rgb),
+            // apples count down and go at 0, so they need at least one
+            Edit::STEPS(value) => {
+                if let Some(steps) = steps {
+                    *steps = u32::max(1, value);
+                }
+            }
+        }
+    }
+
+    pub fn override_circle(&mut self, index: usize, setting: Override) {
+        if let Some(circle) = self.circles.get_mut(index) {
+            match setting {
+                Override::DISTANCE(value) => circle.overrides.max_position_delta = value,
+                Override::COLOR(value) => circle.overrides.max_color_delta = value,
+                Override::RADIUS(change) => circle.overrides.radius_change = change,
+                Override::COLORMODE(mode) => circle.color = circle.color.to_mode(&mode),
+                Override::RESET => circle.overrides = Overrides::new(),
+            }
+        }
+    }
+
+    // each apple may be replaced by its children, as long as the population stays under the cap
+    fn split_apples(&mut self) {
+        if self.config.apple_split_chance <= 0.0 || self.config.apple_children < 2 {
+            return;
+        }
+        let children = self.config.apple_children as usize;
+        let max_apples = self.config.max_apples as usize;
+        let mut population = self.apples.len();
+        let mut apples = Vec::with_capacity(population);
+        let rng = &mut self.rng;
+        for apple in self.apples.drain(..) {
+            if population + children - 1 <= max_apples
+                && random(rng) < self.config.apple_split_chance
+            {
+                population += children - 1;
+                // the first child carries on as the apple, the others are new
+                let mut split = apple.split(children, rng);
+                for child in split.iter_mut().skip(1) {
+                    self.next_id += 1;
+                    child.circle.id = self.next_id;
+                }
+                apples.extend(split);
+            } else {
+                apples.push(apple);
+            }
+        }
+        self.apples = apples;
+    }
+
     fn remaining_apple_steps(&self) -> u32 {
         self.apples
             .iter()
@@ -340,20 +848,54 @@ impl Universe {
             .map_or(0, |apple| apple.steps)
     }
 
+    // ticks to run in the next frame
     pub fn steps(&self) -> u32 {
+        // a run ends on its tick, not somewhere in the frame after it
+        if let Some(Stop::AT(tick)) = self.stop {
+            return u32::min(
+                self.unstopped_steps(),
+                tick.saturating_sub(self.ticks) as u32,
+            );
+        }
+        self.unstopped_steps()
+    }
+
+    fn unstopped_steps(&self) -> u32 {
+        let speed = &self.config.speed;
+        if speed.budget > 0.0 {
+            return u32::max(1, (speed.budget / speed.tick_time) as u32);
+        }
+        // apples are run to the end quickly, up to a point
         u32::max(
-            u32::min(5000, self.remaining_apple_steps()),
-            f64::ceil(self.config.speed.steps() as f64 / (f64::max(1.0, self.circles.len() as f64)))
-                as u32,
+            u32::min(MAX_APPLE_STEPS, self.remaining_apple_steps()),
+            f64::ceil(speed.ticks / (f64::max(1.0, self.circles.len() as f64))) as u32,
         )
     }
 
-    pub fn toggle_size_mode(&mut self) {
-        self.config.size_mode.toggle(
-            &mut self.circle_config,
-            self.config.initial_width,
-            self.config.initial_height,
-        );
+    // how long the last frame's steps took, which the budget adapts to
+    pub fn timed(&mut self, steps: u32, milliseconds: f64) {
+        self.config.speed.timed(steps, milliseconds);
+    }
+
+    // circles and apples outside the new size are brought to its edge
+    pub fn resize(&mut self, width: f64, height: f64) {
+        self.circle_config.width = width;
+        self.circle_config.height = height;
+        for index in 0..self.circles.len() {
+            self.circles[index].position = self.on_canvas(self.circles[index].position.clone());
+        }
+        for index in 0..self.apples.len() {
+            self.apples[index].circle.position =
+                self.on_canvas(self.apples[index].circle.position.clone());
+            self.apples[index].config.width = width;
+            self.apples[index].config.height = height;
+        }
+        self.resize_requested = true;
+    }
+
+    // true once after the size changed, so whoever is drawing can resize the picture
+    pub fn take_resize_request(&mut self) -> bool {
+        std::mem::replace(&mut self.resize_requested, false)
     }
 }
 
@@ -365,11 +907,25 @@ pub struct Apple {
 }
 
 impl Apple {
-    pub fn update(&mut self) -> bool {
-        self.circle.update(&self.config);
+    pub fn update(&mut self, rng: &mut StdRng) -> bool {
+        self.circle.update(&self.config, rng);
         self.steps -= 1;
         self.steps == 0
     }
+
+    fn split(&self, children: usize, rng: &mut StdRng) -> Vec<Apple> {
+        (0..children)
+            .map(|_| {
+                let mut circle = self.circle.clone();
+                circle.color.update(&self.config, rng);
+                Apple {
+                    circle,
+                    config: self.config.clone(),
+                    steps: self.steps,
+                }
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -387,11 +943,14 @@ pub struct Config {
     pub speed: Speed,
     pub radius: f64,
     pub apple_steps: u32,
-    pub bug_checkbox: bool,
+    pub apple_split_chance: f64,
+    pub apple_children: u32,
+    pub max_apples: u32,
+    pub stroke: Stroke,
     pub color_mode: ColorMode,
     pub initial_height: f64,
     pub initial_width: f64,
-    pub size_mode: SizeMode,
+    pub blend: Blend,
 }
 
 #[derive(Clone, Copy)]
@@ -418,42 +977,54 @@ impl Status {
 }
 
 #[derive(Copy, Clone)]
-pub enum Speed {
-    NORMAL,
-    FAST,
+pub struct Speed {
+    // ticks a frame, shared out between the circles
+    pub ticks: f64,
+    // milliseconds a frame can spend ticking, with 0 ticks is used instead
+    pub budget: f64,
+    // how long a tick has been taking lately, for the budget
+    tick_time: f64,
 }
 
 impl Speed {
-    fn steps(self) -> u32 {
-        match self {
-            Speed::NORMAL => 1,
-            Speed::FAST => 3000,
+    // the presets the speed button switches between
+    pub const NORMAL: f64 = 1.0;
+    pub const FAST: f64 = 3000.0;
+
+    fn new() -> Self {
+        Speed {
+            ticks: Speed::NORMAL,
+            budget: 0.0,
+            tick_time: 0.1,
         }
     }
 
-    pub fn next(self) -> Speed {
-        match self {
-            Speed::NORMAL => Speed::FAST,
-            Speed::FAST => Speed::NORMAL,
-        }
+    pub fn is_fast(&self) -> bool {
+        self.ticks >= Speed::FAST
     }
+
     pub fn toggle(&mut self) {
-        *self = self.next()
+        self.ticks = if self.is_fast() {
+            Speed::NORMAL
+        } else {
+            Speed::FAST
+        }
     }
 
-    fn display(self) -> String {
-        String::from(match self {
-            Speed::NORMAL => "🐢",
-            Speed::FAST => "🐇",
-        })
+    // button should display what pressing would do
+    pub fn to_button_display(&self) -> String {
+        String::from(if self.is_fast() { "🐢" } else { "🐇" })
     }
 
-    pub fn to_button_display(self) -> String {
-        self.next().display()
+    fn timed(&mut self, ticks: u32, milliseconds: f64) {
+        if ticks > 0 {
+            let tick_time = f64::max(0.001, milliseconds / ticks as f64);
+            self.tick_time = 0.8 * self.tick_time + 0.2 * tick_time;
+        }
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ColorMode {
     RGB,
     HSL,
@@ -470,6 +1041,20 @@ impl ColorMode {
         *self = self.next()
     }
 
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorMode::RGB => "rgb",
+            ColorMode::HSL => "hsl",
+        }
+    }
+
+    pub fn of_name(name: &str) -> Option<ColorMode> {
+        [ColorMode::RGB, ColorMode::HSL]
+            .iter()
+            .copied()
+            .find(|mode| mode.name() == name)
+    }
+
     fn display(self) -> String {
         String::from(match self {
             ColorMode::RGB => "R",
@@ -482,43 +1067,76 @@ impl ColorMode {
     }
 }
 
-#[derive(Copy, Clone)]
-pub enum SizeMode {
-    NORMAL,
-    GIANT,
-}
-impl SizeMode {
-    pub fn next(self) -> SizeMode {
-        match self {
-            SizeMode::GIANT => SizeMode::NORMAL,
-            SizeMode::NORMAL => SizeMode::GIANT,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::Parameter;
+
+    #[test]
+    fn splitting_into_no_children_does_not_panic() {
+        let mut universe = Universe::new(100.0, 100.0, 1);
+        universe.perform(Action::SET(Parameter::CHILDREN, 0.0));
+        universe.perform(Action::SET(Parameter::SPLIT, 1.0));
+        universe.perform(Action::APPLE(None));
+        for _ in 0..10 {
+            universe.tick();
         }
+        assert_eq!(universe.config.apple_children, 2);
+        assert!(universe.apples.len() <= universe.config.max_apples as usize);
     }
 
-    fn toggle(&mut self, circle_config: &mut CircleConfig, normal_width: f64, normal_height: f64) {
-        *self = self.next();
-        match self {
-            SizeMode::GIANT => {
-                let size = 12000.0;
-                circle_config.width = size;
-                circle_config.height = size;
-            }
+    #[test]
+    fn apples_with_no_steps_do_not_panic() {
+        let mut universe = Universe::new(100.0, 100.0, 1);
+        universe.perform(Action::SET(Parameter::STEPS, 0.0));
+        universe.perform(Action::APPLE(None));
+        universe.tick();
+        assert_eq!(universe.config.apple_steps, 1);
+        assert!(universe.apples.is_empty());
+    }
 
-            SizeMode::NORMAL => {
-                circle_config.width = normal_width;
-                circle_config.height = normal_height;
-            }
-        }
+    #[test]
+    fn ids_follow_an_apple_when_others_finish() {
+        let mut universe = Universe::new(100.0, 100.0, 1);
+        universe.perform(Action::SET(Parameter::STEPS, 2.0));
+        universe.perform(Action::APPLE(None));
+        universe.perform(Action::SET(Parameter::STEPS, 10.0));
+        universe.perform(Action::APPLE(None));
+        let id = universe.apples[1].circle.id;
+        assert_ne!(universe.apples[0].circle.id, id);
+        universe.tick();
+        universe.tick();
+        assert_eq!(universe.apples.len(), 1);
+        assert_eq!(universe.apple_index(id), Some(0));
     }
 
-    fn display(self) -> String {
-        String::from(match self {
-            SizeMode::GIANT => "🐘",
-            SizeMode::NORMAL => "🐁",
-        })
+    fn positions(universe: &Universe) -> Vec<(f64, f64)> {
+        universe
+            .circles
+            .iter()
+            .map(|circle| (circle.position.x, circle.position.y))
+            .collect()
     }
 
-    pub fn to_button_display(self) -> String {
-        self.next().display()
+    #[test]
+    fn universes_draw_their_own_random_numbers() {
+        let mut alone = Universe::new(100.0, 100.0, 7);
+        let mut interleaved = Universe::new(100.0, 100.0, 7);
+        let mut other = Universe::new(100.0, 100.0, 8);
+        for universe in [&mut alone, &mut interleaved, &mut other] {
+            universe.perform(Action::CIRCLE(None));
+        }
+        for _ in 0..20 {
+            alone.tick();
+            other.tick();
+            interleaved.tick();
+            other.perform(Action::CIRCLE(None));
+        }
+        assert_eq!(positions(&alone), positions(&interleaved));
+        let mut replayed = Universe::replay(&interleaved.to_log());
+        for _ in 0..20 {
+            replayed.tick();
+        }
+        assert_eq!(positions(&replayed), positions(&interleaved));
     }
 }
@@ -0,0 +1,155 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::mem;
+
+use crate::action::Action;
+use crate::circle::{Apple, Circle, Universe};
+use crate::{context, default_canvas, layers, panel, update_canvas_size};
+
+// snapshots beyond this are dropped, oldest first
+const MEMORY_BUDGET: usize = 256 * 1024 * 1024;
+
+struct Snapshot {
+    // the layer that was being drawn on
+    layer: u32,
+    universe: Universe,
+    image: web_sys::ImageData,
+}
+
+impl Snapshot {
+    fn take(universe: &Universe) -> Self {
+        let canvas = default_canvas();
+        let image = context(&canvas)
+            .get_image_data(0.0, 0.0, canvas.width() as f64, canvas.height() as f64)
+            .unwrap();
+        Snapshot {
+            layer: layers::active_id(),
+            universe: universe.clone(),
+            image,
+        }
+    }
+
+    fn bytes(&self) -> usize {
+        self.image.width() as usize * self.image.height() as usize * 4
+            + self.universe.circles.len() * mem::size_of::<Circle>()
+            + self.universe.apples.len() * mem::size_of::<Apple>()
+    }
+
+    fn restore(self, universe: &mut Universe) {
+        layers::select(self.layer, universe);
+        let canvas = default_canvas();
+        if canvas.width() != self.image.width() || canvas.height() != self.image.height() {
+            update_canvas_size(self.image.height() as f64, self.image.width() as f64);
+        }
+        context(&canvas)
+            .put_image_data(&self.image, 0.0, 0.0)
+            .unwrap();
+        *universe = self.universe;
+        // only the active layer was saved, the others follow it back to its size
+        let (width, height) = (universe.circle_config.width, universe.circle_config.height);
+        layers::for_each_inactive(|other, _| {
+            if other.circle_config.width != width || other.circle_config.height != height {
+                other.perform(Action::RESIZE(width, height));
+            }
+        });
+        panel::refresh(universe);
+    }
+}
+
+struct History {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+    // the control that made the last checkpoint, so dragging a slider is one step
+    last_key: Option<String>,
+}
+
+impl History {
+    fn bytes(&self) -> usize {
+        self.undo
+            .iter()
+            .chain(self.redo.iter())
+            .map(Snapshot::bytes)
+            .sum()
+    }
+
+    // drops the oldest undo steps first, then the redo steps furthest away
+    fn trim(&mut self) {
+        while self.bytes() > MEMORY_BUDGET {
+            if self.undo.len() > 1 {
+                self.undo.pop_front();
+            } else if self.redo.len() > 1 {
+                self.redo.remove(0);
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn push(&mut self, snapshot: Snapshot) {
+        self.undo.push_back(snapshot);
+        self.redo.clear();
+        self.trim();
+    }
+}
+
+thread_local! {
+    static HISTORY: RefCell<History> = RefCell::new(History {
+        undo: VecDeque::new(),
+        redo: vec![],
+        last_key: None,
+    });
+}
+
+// records the state from before an action, call it right before changing anything
+pub fn checkpoint(universe: &Universe) {
+    HISTORY.with(|history| {
+        let mut history = history.borrow_mut();
+        history.last_key = None;
+        history.push(Snapshot::take(universe));
+    })
+}
+
+// like checkpoint, but repeated changes to the same control only record the first state
+pub fn checkpoint_control(key: &str, universe: &Universe) {
+    HISTORY.with(|history| {
+        let mut history = history.borrow_mut();
+        if history.last_key.as_deref() == Some(key) {
+            return;
+        }
+        history.last_key = Some(String::from(key));
+        history.push(Snapshot::take(universe));
+    })
+}
+
+pub fn clear() {
+    HISTORY.with(|history| {
+        let mut history = history.borrow_mut();
+        history.last_key = None;
+        history.undo.clear();
+        history.redo.clear();
+    })
+}
+
+pub fn undo(universe: &mut Universe) {
+    HISTORY.with(|history| {
+        let mut history = history.borrow_mut();
+        history.last_key = None;
+        if let Some(snapshot) = history.undo.pop_back() {
+            history.redo.push(Snapshot::take(universe));
+            history.trim();
+            snapshot.restore(universe);
+        }
+    })
+}
+
+pub fn redo(universe: &mut Universe) {
+    HISTORY.with(|history| {
+        let mut history = history.borrow_mut();
+        history.last_key = None;
+        if let Some(snapshot) = history.redo.pop() {
+            history.undo.push_back(Snapshot::take(universe));
+            history.trim();
+            snapshot.restore(universe);
+        }
+    })
+}
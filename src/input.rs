@@ -0,0 +1,189 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::action::{Action, Target};
+use crate::circle::{Position, Universe};
+use crate::overlay_canvas;
+use crate::{history, selection, viewport_ui};
+
+// maps a point on the page to a point on the canvas, wherever the view has moved it
+fn canvas_position(client_x: f64, client_y: f64) -> Position {
+    viewport_ui::screen_to_world(client_x, client_y)
+}
+
+fn client_position(event: &web_sys::PointerEvent) -> (f64, f64) {
+    (event.client_x() as f64, event.client_y() as f64)
+}
+
+// how far in screen pixels a finger can wander and still count as a tap
+const TAP_DISTANCE: f64 = 10.0;
+
+// a finger that landed on empty canvas
+struct Touch {
+    down: (f64, f64),
+    now: (f64, f64),
+}
+
+impl Touch {
+    fn is_tap(&self) -> bool {
+        f64::hypot(self.now.0 - self.down.0, self.now.1 - self.down.1) <= TAP_DISTANCE
+    }
+}
+
+// a circle held by a pointer, by id since circles can come and go while it's held
+struct Drag {
+    id: u64,
+    // nothing is checkpointed for a click that doesn't move the circle
+    moved: bool,
+}
+
+struct Pointers {
+    // pointer id -> the circle being dragged by that pointer
+    drags: HashMap<i32, Drag>,
+    // a tap spawns a circle, two fingers moving together pinch
+    touches: HashMap<i32, Touch>,
+    pinching: bool,
+    // the pointer dragging the view around and where it last was
+    pan: Option<(i32, (f64, f64))>,
+}
+
+impl Pointers {
+    fn pinch_points(&self) -> Option<((f64, f64), (f64, f64))> {
+        let mut points = self.touches.values().map(|touch| touch.now);
+        match (points.next(), points.next(), points.next()) {
+            (Some(first), Some(second), None) => Some((first, second)),
+            _ => None,
+        }
+    }
+}
+
+pub fn listen_for_pointer(universe: &Arc<Mutex<Universe>>) {
+    let canvas = overlay_canvas();
+    let pointers = Rc::new(RefCell::new(Pointers {
+        drags: HashMap::new(),
+        touches: HashMap::new(),
+        pinching: false,
+        pan: None,
+    }));
+
+    let down_universe = Arc::clone(universe);
+    let down_pointers = Rc::clone(&pointers);
+    let on_pointer_down = Closure::wrap(Box::new(move |event: web_sys::PointerEvent| {
+        let canvas = overlay_canvas();
+        let (client_x, client_y) = client_position(&event);
+        let position = canvas_position(client_x, client_y);
+        let mut universe = down_universe.lock().unwrap();
+        let mut pointers = down_pointers.borrow_mut();
+
+        // the middle button, or alt with any, drags the view instead of drawing
+        if event.button() == 1 || event.alt_key() {
+            event.prevent_default();
+            pointers.pan = Some((event.pointer_id(), (client_x, client_y)));
+            canvas.set_pointer_capture(event.pointer_id()).unwrap();
+            return;
+        }
+
+        match (universe.circle_at(&position), universe.apple_at(&position)) {
+            (Some(index), _) => {
+                selection::select(Some(Target::CIRCLE(index)), &universe);
+                let id = universe.circles[index].id;
+                pointers
+                    .drags
+                    .insert(event.pointer_id(), Drag { id, moved: false });
+                canvas.set_pointer_capture(event.pointer_id()).unwrap();
+            }
+            (None, Some(index)) => selection::select(Some(Target::APPLE(index)), &universe),
+            (None, None) if event.pointer_type() == "touch" => {
+                pointers.touches.insert(
+                    event.pointer_id(),
+                    Touch {
+                        down: (client_x, client_y),
+                        now: (client_x, client_y),
+                    },
+                );
+                pointers.pinching = pointers.touches.len() > 1;
+                canvas.set_pointer_capture(event.pointer_id()).unwrap();
+            }
+            (None, None) => {
+                history::checkpoint(&universe);
+                if event.shift_key() {
+                    universe.perform(Action::APPLE(Some(position)));
+                } else {
+                    universe.perform(Action::CIRCLE(Some(position)));
+                }
+            }
+        }
+    }) as Box<dyn FnMut(_)>);
+
+    let move_universe = Arc::clone(universe);
+    let move_pointers = Rc::clone(&pointers);
+    let on_pointer_move = Closure::wrap(Box::new(move |event: web_sys::PointerEvent| {
+        let (client_x, client_y) = client_position(&event);
+        let mut pointers = move_pointers.borrow_mut();
+        let from = pointers.pinch_points();
+
+        if let Some((id, (last_x, last_y))) = pointers.pan {
+            if id == event.pointer_id() {
+                viewport_ui::pan(client_x - last_x, client_y - last_y);
+                pointers.pan = Some((id, (client_x, client_y)));
+                return;
+            }
+        }
+
+        if let Some(drag) = pointers.drags.get_mut(&event.pointer_id()) {
+            let position = canvas_position(client_x, client_y);
+            let mut universe = move_universe.lock().unwrap();
+            if let Some(index) = universe.circle_index(drag.id) {
+                if !drag.moved {
+                    history::checkpoint(&universe);
+                    drag.moved = true;
+                }
+                universe.perform(Action::MOVE(index, position));
+            }
+        } else if let Some(touch) = pointers.touches.get_mut(&event.pointer_id()) {
+            touch.now = (client_x, client_y);
+            if let (true, Some(from), Some(to)) = (pointers.pinching, from, pointers.pinch_points())
+            {
+                viewport_ui::pinch(from, to);
+            }
+        }
+    }) as Box<dyn FnMut(_)>);
+
+    let up_universe = Arc::clone(universe);
+    let up_pointers = Rc::clone(&pointers);
+    let on_pointer_up = Closure::wrap(Box::new(move |event: web_sys::PointerEvent| {
+        let mut pointers = up_pointers.borrow_mut();
+        pointers.drags.remove(&event.pointer_id());
+        if let Some((id, _)) = pointers.pan {
+            if id == event.pointer_id() {
+                pointers.pan = None;
+            }
+        }
+
+        if let Some(touch) = pointers.touches.remove(&event.pointer_id()) {
+            // a finger dragged across empty canvas without a second one does nothing
+            if !pointers.pinching && touch.is_tap() && event.type_() == "pointerup" {
+                let position = canvas_position(touch.down.0, touch.down.1);
+                let mut universe = up_universe.lock().unwrap();
+                history::checkpoint(&universe);
+                universe.perform(Action::CIRCLE(Some(position)));
+            }
+            if pointers.touches.is_empty() {
+                pointers.pinching = false;
+            }
+        }
+    }) as Box<dyn FnMut(_)>);
+
+    canvas.set_onpointerdown(Some(on_pointer_down.as_ref().unchecked_ref()));
+    canvas.set_onpointermove(Some(on_pointer_move.as_ref().unchecked_ref()));
+    canvas.set_onpointerup(Some(on_pointer_up.as_ref().unchecked_ref()));
+    canvas.set_onpointercancel(Some(on_pointer_up.as_ref().unchecked_ref()));
+
+    on_pointer_down.forget();
+    on_pointer_move.forget();
+    on_pointer_up.forget();
+}
@@ -0,0 +1,507 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::circle::Universe;
+use crate::registry::{self, Kind};
+use crate::{
+    body, document, panel, sidebar, storage, ADD_BUTTON_ID, APPLE_BUTTON_ID, FREEZE_BUTTON_ID,
+    RECORD_BUTTON_ID, REDO_BUTTON_ID, SAVE_BUTTON_ID, SPEED_BUTTON_ID, START_STOP_BUTTON_ID,
+    STEP_BUTTON_ID, TRASH_BUTTON_ID, UNDO_BUTTON_ID,
+};
+
+const STORAGE_KEY: &str = "keymap";
+const HELP_OVERLAY_ID: &str = "help-overlay";
+const HELP_BUTTON_ID: &str = "help-button";
+const SELECTED_SLIDER_CLASS: &str = "slider selected";
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum KeyAction {
+    PAUSE,
+    STEP,
+    SPEED,
+    FREEZE,
+    SAVE,
+    TRASH,
+    UNDO,
+    REDO,
+    CIRCLE,
+    APPLE,
+    PREVIOUS,
+    NEXT,
+    DECREASE,
+    INCREASE,
+    RECORD,
+    HELP,
+    HIDE,
+    // clicks the control of a toggle or choice in the registry
+    TUNABLE(&'static str),
+}
+
+const FIXED_ACTIONS: [KeyAction; 17] = [
+    KeyAction::PAUSE,
+    KeyAction::STEP,
+    KeyAction::SPEED,
+    KeyAction::FREEZE,
+    KeyAction::SAVE,
+    KeyAction::TRASH,
+    KeyAction::UNDO,
+    KeyAction::REDO,
+    KeyAction::CIRCLE,
+    KeyAction::APPLE,
+    KeyAction::PREVIOUS,
+    KeyAction::NEXT,
+    KeyAction::DECREASE,
+    KeyAction::INCREASE,
+    KeyAction::RECORD,
+    KeyAction::HELP,
+    KeyAction::HIDE,
+];
+
+// every toggle and choice can have a key, the ones with a default are listed
+fn all_actions() -> Vec<KeyAction> {
+    let mut actions = FIXED_ACTIONS.to_vec();
+    actions.extend(
+        registry::tunables()
+            .iter()
+            .filter(|tunable| tunable.key.is_some())
+            .filter(|tunable| matches!(tunable.kind, Kind::TOGGLE { .. } | Kind::CHOICE { .. }))
+            .map(|tunable| KeyAction::TUNABLE(tunable.name)),
+    );
+    actions
+}
+
+impl KeyAction {
+    fn name(self) -> &'static str {
+        match self {
+            KeyAction::PAUSE => "pause",
+            KeyAction::STEP => "step",
+            KeyAction::SPEED => "speed",
+            KeyAction::FREEZE => "freeze",
+            KeyAction::SAVE => "save",
+            KeyAction::TRASH => "trash",
+            KeyAction::UNDO => "undo",
+            KeyAction::REDO => "redo",
+            KeyAction::CIRCLE => "circle",
+            KeyAction::APPLE => "apple",
+            KeyAction::PREVIOUS => "previous",
+            KeyAction::NEXT => "next",
+            KeyAction::DECREASE => "decrease",
+            KeyAction::INCREASE => "increase",
+            KeyAction::RECORD => "record",
+            KeyAction::HELP => "help",
+            KeyAction::HIDE => "hide",
+            KeyAction::TUNABLE(name) => name,
+        }
+    }
+
+    fn of_name(name: &str) -> Option<KeyAction> {
+        all_actions()
+            .into_iter()
+            .find(|action| action.name() == name)
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            KeyAction::PAUSE => "Start / stop",
+            KeyAction::STEP => "Run one tick",
+            KeyAction::SPEED => "Toggle speed",
+            KeyAction::FREEZE => "Freeze circles",
+            KeyAction::SAVE => "Save image",
+            KeyAction::TRASH => "Clear everything",
+            KeyAction::UNDO => "Undo",
+            KeyAction::REDO => "Redo",
+            KeyAction::CIRCLE => "Add a circle",
+            KeyAction::APPLE => "Add an apple",
+            KeyAction::PREVIOUS => "Select previous slider",
+            KeyAction::NEXT => "Select next slider",
+            KeyAction::DECREASE => "Decrease selected slider",
+            KeyAction::INCREASE => "Increase selected slider",
+            KeyAction::RECORD => "Start / stop recording",
+            KeyAction::HELP => "Show / hide this help",
+            KeyAction::HIDE => "Hide / show the controls",
+            KeyAction::TUNABLE(name) => registry::tunable(name).map_or("", |tunable| tunable.title),
+        }
+    }
+
+    fn default_key(self) -> &'static str {
+        match self {
+            KeyAction::PAUSE => " ",
+            KeyAction::STEP => ".",
+            KeyAction::SPEED => "f",
+            KeyAction::FREEZE => "i",
+            KeyAction::SAVE => "s",
+            KeyAction::TRASH => "Delete",
+            KeyAction::UNDO => "Ctrl+z",
+            KeyAction::REDO => "Ctrl+y",
+            KeyAction::CIRCLE => "+",
+            KeyAction::APPLE => "a",
+            KeyAction::PREVIOUS => "ArrowUp",
+            KeyAction::NEXT => "ArrowDown",
+            KeyAction::DECREASE => "ArrowLeft",
+            KeyAction::INCREASE => "ArrowRight",
+            KeyAction::RECORD => "r",
+            KeyAction::HELP => "?",
+            KeyAction::HIDE => "h",
+            KeyAction::TUNABLE(name) => registry::tunable(name)
+                .and_then(|tunable| tunable.key)
+                .unwrap_or(""),
+        }
+    }
+
+    // the control that the action stands in for, if it is a plain click
+    fn control_id(self) -> Option<String> {
+        let id = match self {
+            KeyAction::PAUSE => Some(START_STOP_BUTTON_ID),
+            KeyAction::STEP => Some(STEP_BUTTON_ID),
+            KeyAction::SPEED => Some(SPEED_BUTTON_ID),
+            KeyAction::FREEZE => Some(FREEZE_BUTTON_ID),
+            KeyAction::SAVE => Some(SAVE_BUTTON_ID),
+            KeyAction::TRASH => Some(TRASH_BUTTON_ID),
+            KeyAction::UNDO => Some(UNDO_BUTTON_ID),
+            KeyAction::REDO => Some(REDO_BUTTON_ID),
+            KeyAction::CIRCLE => Some(ADD_BUTTON_ID),
+            KeyAction::APPLE => Some(APPLE_BUTTON_ID),
+            KeyAction::RECORD => Some(RECORD_BUTTON_ID),
+            KeyAction::HELP => Some(HELP_BUTTON_ID),
+            KeyAction::HIDE => Some(sidebar::HIDE_BUTTON_ID),
+            KeyAction::TUNABLE(name) => return registry::tunable(name).map(|tunable| tunable.id()),
+            KeyAction::PREVIOUS | KeyAction::NEXT | KeyAction::DECREASE | KeyAction::INCREASE => {
+                None
+            }
+        };
+        id.map(String::from)
+    }
+}
+
+// modifiers are part of the binding, so ctrl+z and z can do different things
+fn key_name(event: &web_sys::KeyboardEvent) -> String {
+    let mut name = String::new();
+    if event.ctrl_key() || event.meta_key() {
+        name.push_str("Ctrl+");
+    }
+    if event.alt_key() {
+        name.push_str("Alt+");
+    }
+    name.push_str(&event.key());
+    name
+}
+
+fn is_modifier(key: &str) -> bool {
+    matches!(key, "Control" | "Shift" | "Alt" | "Meta")
+}
+
+fn display_key(key: &str) -> &str {
+    match key {
+        " " => "Space",
+        "" => "—",
+        key => key,
+    }
+}
+
+struct Keymap {
+    bindings: Vec<(KeyAction, String)>,
+    // the action waiting for its next key press to become its binding
+    rebinding: Option<KeyAction>,
+    selected_slider: Option<u32>,
+}
+
+impl Keymap {
+    fn new() -> Self {
+        Keymap {
+            bindings: all_actions()
+                .into_iter()
+                .map(|action| (action, String::from(action.default_key())))
+                .collect(),
+            rebinding: None,
+            selected_slider: None,
+        }
+    }
+
+    fn load() -> Self {
+        let mut keymap = Keymap::new();
+        let saved = storage().and_then(|storage| storage.get_item(STORAGE_KEY).unwrap());
+        if let Some(saved) = saved {
+            for line in saved.lines() {
+                if let Some((name, key)) = line.split_once('=') {
+                    if let Some(action) = KeyAction::of_name(name) {
+                        keymap.bind(action, key);
+                    }
+                }
+            }
+        }
+        keymap
+    }
+
+    fn save(&self) {
+        let saved = self
+            .bindings
+            .iter()
+            .map(|(action, key)| format!("{}={}", action.name(), key))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if let Some(storage) = storage() {
+            storage.set_item(STORAGE_KEY, &saved).unwrap();
+        }
+    }
+
+    fn key(&self, action: KeyAction) -> &str {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map_or("", |(_, key)| key)
+    }
+
+    fn action(&self, key: &str) -> Option<KeyAction> {
+        self.bindings
+            .iter()
+            .find(|(_, bound)| bound == key)
+            .map(|(action, _)| *action)
+    }
+
+    // an action that already used the key swaps over to the old key
+    fn bind(&mut self, action: KeyAction, key: &str) {
+        let old_key = String::from(self.key(action));
+        for (bound_action, bound_key) in self.bindings.iter_mut() {
+            if *bound_action == action {
+                *bound_key = String::from(key);
+            } else if bound_key == key {
+                *bound_key = old_key.clone();
+            }
+        }
+    }
+}
+
+fn sliders() -> Vec<web_sys::HtmlInputElement> {
+    let collection = document().get_elements_by_class_name("slider");
+    (0..collection.length())
+        .filter_map(|i| collection.item(i))
+        .filter_map(|element| element.dyn_into::<web_sys::HtmlInputElement>().ok())
+        .collect()
+}
+
+fn select_slider(keymap: &mut Keymap, offset: i32) {
+    let sliders = sliders();
+    if sliders.is_empty() {
+        return;
+    }
+    let count = sliders.len() as i32;
+    let index = match keymap.selected_slider {
+        None if offset < 0 => count - 1,
+        None => 0,
+        Some(index) => (index as i32 + offset).rem_euclid(count),
+    };
+    for slider in sliders.iter() {
+        slider.set_class_name("slider");
+    }
+    sliders[index as usize].set_class_name(SELECTED_SLIDER_CLASS);
+    keymap.selected_slider = Some(index as u32);
+}
+
+fn nudge_selected_slider(keymap: &Keymap, up: bool) {
+    let slider = match keymap
+        .selected_slider
+        .and_then(|index| sliders().into_iter().nth(index as usize))
+    {
+        Some(slider) => slider,
+        None => return,
+    };
+    let step = slider.step().parse::<f64>().unwrap_or(1.0);
+    let delta = if up { step } else { -step };
+    // the browser clamps the value to the slider's range and snaps it to its step
+    slider.set_value_as_number(slider.value_as_number() + delta);
+    slider
+        .dispatch_event(&web_sys::Event::new("input").unwrap())
+        .unwrap();
+}
+
+// typing into a number box shouldn't also trigger shortcuts
+fn is_typing() -> bool {
+    document()
+        .active_element()
+        .and_then(|element| element.dyn_into::<web_sys::HtmlInputElement>().ok())
+        .map_or(false, |input| {
+            !matches!(
+                input.type_().as_str(),
+                "range" | "checkbox" | "radio" | "color"
+            )
+        })
+}
+
+// clicking a dropdown or a radio group only focuses it, so a key moves it to its next value
+fn click(element: web_sys::Element) {
+    if let Some(select) = element.dyn_ref::<web_sys::HtmlSelectElement>() {
+        let length = select.length() as i32;
+        if length > 0 {
+            select.set_selected_index((select.selected_index() + 1) % length);
+            select
+                .dispatch_event(&web_sys::Event::new("change").unwrap())
+                .unwrap();
+        }
+        return;
+    }
+    if element.class_list().contains("radio-group") {
+        let radios = element.get_elements_by_tag_name("input");
+        let checked = (0..radios.length()).find(|index| {
+            radios
+                .item(*index)
+                .unwrap()
+                .dyn_into::<web_sys::HtmlInputElement>()
+                .unwrap()
+                .checked()
+        });
+        let next = checked.map_or(0, |index| (index + 1) % radios.length());
+        if let Some(radio) = radios.item(next) {
+            radio.dyn_into::<web_sys::HtmlElement>().unwrap().click();
+        }
+        return;
+    }
+    element.dyn_into::<web_sys::HtmlElement>().unwrap().click();
+}
+
+fn perform(keymap: &mut Keymap, action: KeyAction, universe: &Arc<Mutex<Universe>>) {
+    match action.control_id() {
+        Some(id) => {
+            if let Some(element) = document().get_element_by_id(&id) {
+                click(element);
+                // a button can change what other controls show
+                panel::refresh(&universe.lock().unwrap());
+            }
+        }
+        None => match action {
+            KeyAction::PREVIOUS => select_slider(keymap, -1),
+            KeyAction::NEXT => select_slider(keymap, 1),
+            KeyAction::DECREASE => nudge_selected_slider(keymap, false),
+            KeyAction::INCREASE => nudge_selected_slider(keymap, true),
+            _ => {}
+        },
+    }
+}
+
+fn toggle_help() {
+    let overlay = document()
+        .get_element_by_id(HELP_OVERLAY_ID)
+        .unwrap()
+        .dyn_into::<web_sys::HtmlElement>()
+        .unwrap();
+    overlay.set_hidden(!overlay.hidden());
+}
+
+fn render_help(keymap: &Rc<RefCell<Keymap>>) {
+    let overlay = document().get_element_by_id(HELP_OVERLAY_ID).unwrap();
+    overlay.set_inner_html("");
+
+    let title = document().create_element("p").unwrap();
+    title.set_text_content(Some("Keyboard shortcuts — click a key to rebind it"));
+    overlay.append_child(&title).unwrap();
+
+    for action in all_actions() {
+        let row = document().create_element("div").unwrap();
+        row.set_class_name("binding");
+
+        let description = document().create_element("span").unwrap();
+        description.set_text_content(Some(action.description()));
+
+        let key_button = document()
+            .create_element("button")
+            .unwrap()
+            .dyn_into::<web_sys::HtmlButtonElement>()
+            .unwrap();
+        let text = if keymap.borrow().rebinding == Some(action) {
+            String::from("press a key…")
+        } else {
+            String::from(display_key(keymap.borrow().key(action)))
+        };
+        key_button.set_inner_text(&text);
+
+        let keymap_clone = Rc::clone(keymap);
+        let on_click_handler = Closure::wrap(Box::new(move || {
+            keymap_clone.borrow_mut().rebinding = Some(action);
+            render_help(&keymap_clone);
+        }) as Box<dyn FnMut()>);
+        key_button.set_onclick(Some(on_click_handler.as_ref().unchecked_ref()));
+        on_click_handler.forget();
+
+        row.append_child(&description).unwrap();
+        row.append_child(&key_button).unwrap();
+        overlay.append_child(&row).unwrap();
+    }
+
+    let reset_button = document()
+        .create_element("button")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlButtonElement>()
+        .unwrap();
+    reset_button.set_inner_text("Reset to defaults");
+    let keymap_clone = Rc::clone(keymap);
+    let on_click_handler = Closure::wrap(Box::new(move || {
+        *keymap_clone.borrow_mut() = Keymap::new();
+        keymap_clone.borrow().save();
+        render_help(&keymap_clone);
+    }) as Box<dyn FnMut()>);
+    reset_button.set_onclick(Some(on_click_handler.as_ref().unchecked_ref()));
+    on_click_handler.forget();
+    overlay.append_child(&reset_button).unwrap();
+}
+
+pub fn listen_for_keys(universe: &Arc<Mutex<Universe>>) -> Result<(), JsValue> {
+    let keymap = Rc::new(RefCell::new(Keymap::load()));
+
+    let overlay = document()
+        .create_element("div")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlDivElement>()
+        .unwrap();
+    overlay.set_id(HELP_OVERLAY_ID);
+    overlay.set_class_name("control");
+    overlay.set_hidden(true);
+    body().append_child(&overlay)?;
+    render_help(&keymap);
+
+    let help_button = document()
+        .create_element("button")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlButtonElement>()
+        .unwrap();
+    help_button.set_id(HELP_BUTTON_ID);
+    help_button.set_inner_text("⌨️");
+    let on_click_handler = Closure::wrap(Box::new(toggle_help) as Box<dyn FnMut()>);
+    help_button.set_onclick(Some(on_click_handler.as_ref().unchecked_ref()));
+    on_click_handler.forget();
+    body().append_child(&help_button)?;
+
+    let keymap_clone = Rc::clone(&keymap);
+    let universe = Arc::clone(universe);
+    let on_key_down_handler = Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
+        if is_modifier(&event.key()) {
+            return;
+        }
+
+        let rebinding = keymap_clone.borrow_mut().rebinding.take();
+        if let Some(action) = rebinding {
+            event.prevent_default();
+            if event.key() != "Escape" {
+                keymap_clone.borrow_mut().bind(action, &key_name(&event));
+                keymap_clone.borrow().save();
+            }
+            render_help(&keymap_clone);
+            return;
+        }
+
+        if is_typing() {
+            return;
+        }
+
+        let action = keymap_clone.borrow().action(&key_name(&event));
+        if let Some(action) = action {
+            event.prevent_default();
+            perform(&mut keymap_clone.borrow_mut(), action, &universe);
+        }
+    }) as Box<dyn FnMut(_)>);
+    document().set_onkeydown(Some(on_key_down_handler.as_ref().unchecked_ref()));
+    on_key_down_handler.forget();
+
+    Ok(())
+}
@@ -0,0 +1,456 @@
+use std::cell::RefCell;
+use std::mem;
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::action::Action;
+use crate::blend::{Blend, ALL_BLENDS};
+use crate::circle::Universe;
+use crate::registry::Group;
+use crate::{
+    background_ui, body, context, document, get_canvas_by_id, history, overlay_canvas, panel,
+    selection, sidebar,
+};
+
+const LAYERS_DIV_ID: &str = "layers";
+
+struct Layer {
+    id: u32,
+    // the active layer's universe is the shared one that the controls change
+    universe: Option<Universe>,
+    canvas: web_sys::HtmlCanvasElement,
+    visible: bool,
+    opacity: f64,
+    blend: Blend,
+}
+
+impl Layer {
+    // the page mixes the layers with css, flatten does the same on a canvas
+    fn apply_style(&self) {
+        let style = self.canvas.style();
+        let visibility = if self.visible { "visible" } else { "hidden" };
+        style.set_property("visibility", visibility).unwrap();
+        style
+            .set_property("opacity", &self.opacity.to_string())
+            .unwrap();
+        style
+            .set_property("mix-blend-mode", self.blend.mix_blend_mode())
+            .unwrap();
+    }
+}
+
+// bottom to top
+struct Layers {
+    layers: Vec<Layer>,
+    active: usize,
+    next_id: u32,
+}
+
+impl Layers {
+    fn index_of(&self, id: u32) -> Option<usize> {
+        self.layers.iter().position(|layer| layer.id == id)
+    }
+
+    fn layer_mut(&mut self, id: u32) -> Option<&mut Layer> {
+        self.layers.iter_mut().find(|layer| layer.id == id)
+    }
+
+    fn select(&mut self, index: usize, universe: &mut Universe) {
+        if index == self.active {
+            return;
+        }
+        let selected = self.layers[index].universe.take().unwrap();
+        self.layers[self.active].universe = Some(mem::replace(universe, selected));
+        self.active = index;
+    }
+
+    // the dom order of the canvases is the stacking order
+    fn restack(&self) {
+        let overlay = overlay_canvas();
+        for layer in self.layers.iter() {
+            body().insert_before(&layer.canvas, Some(&overlay)).unwrap();
+        }
+    }
+}
+
+thread_local! {
+    static LAYERS: RefCell<Layers> = RefCell::new(Layers {
+        layers: vec![Layer {
+            id: 1,
+            universe: None,
+            canvas: get_canvas_by_id("canvas"),
+            visible: true,
+            opacity: 1.0,
+            blend: Blend::NORMAL,
+        }],
+        active: 0,
+        next_id: 2,
+    });
+    static PANEL_UNIVERSE: RefCell<Option<Arc<Mutex<Universe>>>> = RefCell::new(None);
+}
+
+pub fn active_canvas() -> web_sys::HtmlCanvasElement {
+    LAYERS.with(|layers| {
+        let layers = layers.borrow();
+        layers.layers[layers.active].canvas.clone()
+    })
+}
+
+pub fn active_id() -> u32 {
+    LAYERS.with(|layers| {
+        let layers = layers.borrow();
+        layers.layers[layers.active].id
+    })
+}
+
+pub fn canvases() -> Vec<web_sys::HtmlCanvasElement> {
+    LAYERS.with(|layers| {
+        layers
+            .borrow()
+            .layers
+            .iter()
+            .map(|layer| layer.canvas.clone())
+            .collect()
+    })
+}
+
+// makes the layer with this id the one the controls change, swapping its universe in
+pub fn select(id: u32, universe: &mut Universe) {
+    LAYERS.with(|layers| {
+        let mut layers = layers.borrow_mut();
+        if let Some(index) = layers.index_of(id) {
+            layers.select(index, universe);
+        }
+    });
+    render_panel();
+    // the controls and inspector still show the layer that was left
+    panel::refresh(universe);
+    selection::refresh(universe);
+}
+
+// f must not use the layers itself
+pub fn for_each_inactive(mut f: impl FnMut(&mut Universe, &web_sys::HtmlCanvasElement)) {
+    LAYERS.with(|layers| {
+        for layer in layers.borrow_mut().layers.iter_mut() {
+            if let Some(universe) = layer.universe.as_mut() {
+                f(universe, &layer.canvas);
+            }
+        }
+    })
+}
+
+// all the visible layers mixed together on the background, like the page shows them
+pub fn flatten() -> web_sys::HtmlCanvasElement {
+    let overlay = overlay_canvas();
+    let canvas = document()
+        .create_element("canvas")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+    canvas.set_width(overlay.width());
+    canvas.set_height(overlay.height());
+    background_ui::paint(&canvas);
+    paint(&context(&canvas));
+    canvas
+}
+
+// draws the visible layers mixed together, at whatever transform the context has
+pub fn paint(context: &web_sys::CanvasRenderingContext2d) {
+    LAYERS.with(|layers| {
+        for layer in layers.borrow().layers.iter().filter(|layer| layer.visible) {
+            context.set_global_alpha(layer.opacity);
+            context
+                .set_global_composite_operation(layer.blend.composite_operation())
+                .unwrap();
+            context
+                .draw_image_with_html_canvas_element(&layer.canvas, 0.0, 0.0)
+                .unwrap();
+        }
+    });
+    context.set_global_alpha(1.0);
+    context
+        .set_global_composite_operation("source-over")
+        .unwrap();
+}
+
+fn add(universe: &mut Universe) {
+    let overlay = overlay_canvas();
+    let canvas = document()
+        .create_element("canvas")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+    canvas.set_class_name("layer");
+    canvas.set_width(overlay.width());
+    canvas.set_height(overlay.height());
+    // keep up with any pinch zoom and with being shrunk to fit the window
+    for property in ["transform-origin", "transform", "width", "height"].iter() {
+        let value = overlay.style().get_property_value(property).unwrap();
+        canvas.style().set_property(property, &value).unwrap();
+    }
+
+    let mut new_universe = Universe::new(
+        universe.circle_config.width,
+        universe.circle_config.height,
+        js_sys::Date::now() as u64,
+    );
+    new_universe.perform(Action::CIRCLE(None));
+    new_universe.perform(Action::CIRCLE(None));
+
+    LAYERS.with(|layers| {
+        let mut layers = layers.borrow_mut();
+        let layer = Layer {
+            id: layers.next_id,
+            universe: Some(new_universe),
+            canvas,
+            visible: true,
+            opacity: 1.0,
+            blend: Blend::NORMAL,
+        };
+        layer.apply_style();
+        layers.next_id += 1;
+        layers.layers.push(layer);
+        layers.restack();
+        let index = layers.layers.len() - 1;
+        layers.select(index, universe);
+    })
+}
+
+fn remove(id: u32, universe: &mut Universe) {
+    LAYERS.with(|layers| {
+        let mut layers = layers.borrow_mut();
+        let index = match layers.index_of(id) {
+            Some(index) if layers.layers.len() > 1 => index,
+            _ => return,
+        };
+        if index == layers.active {
+            let neighbour = if index == 0 { 1 } else { index - 1 };
+            layers.select(neighbour, universe);
+        }
+        let layer = layers.layers.remove(index);
+        layer.canvas.remove();
+        if layers.active > index {
+            layers.active -= 1;
+        }
+    });
+    // the undo history may have snapshots of the removed layer
+    history::clear();
+}
+
+fn move_up(id: u32, up: bool) {
+    LAYERS.with(|layers| {
+        let mut layers = layers.borrow_mut();
+        let index = match layers.index_of(id) {
+            Some(index) => index,
+            None => return,
+        };
+        let other = match up {
+            true if index + 1 < layers.layers.len() => index + 1,
+            false if index > 0 => index - 1,
+            _ => return,
+        };
+        layers.layers.swap(index, other);
+        if layers.active == index {
+            layers.active = other;
+        } else if layers.active == other {
+            layers.active = index;
+        }
+        layers.restack();
+    })
+}
+
+fn update(id: u32, f: impl FnOnce(&mut Layer)) {
+    LAYERS.with(|layers| {
+        if let Some(layer) = layers.borrow_mut().layer_mut(id) {
+            f(layer);
+            layer.apply_style();
+        }
+    })
+}
+
+fn new_button(text: &str, on_click: impl FnMut() + 'static) -> web_sys::HtmlButtonElement {
+    let button = document()
+        .create_element("button")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlButtonElement>()
+        .unwrap();
+    button.set_inner_text(text);
+    let on_click_handler = Closure::wrap(Box::new(on_click) as Box<dyn FnMut()>);
+    button.set_onclick(Some(on_click_handler.as_ref().unchecked_ref()));
+    on_click_handler.forget();
+    button
+}
+
+fn new_input(type_: &str) -> web_sys::HtmlInputElement {
+    let input = document()
+        .create_element("input")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    input.set_type(type_);
+    input
+}
+
+fn layer_row(
+    universe: &Arc<Mutex<Universe>>,
+    id: u32,
+    number: usize,
+    active: bool,
+    visible: bool,
+    opacity: f64,
+    blend: Blend,
+) -> web_sys::Element {
+    let row = document().create_element("div").unwrap();
+    row.set_class_name(if active {
+        "layer-row active"
+    } else {
+        "layer-row"
+    });
+
+    let universe_clone = Arc::clone(universe);
+    let text = if active {
+        format!("🖌️ {}", number)
+    } else {
+        number.to_string()
+    };
+    let select_button = new_button(&text, move || {
+        select(id, &mut universe_clone.lock().unwrap());
+    });
+    select_button.set_title("Draw on this layer");
+    row.append_child(&select_button).unwrap();
+
+    let visible_checkbox = new_input("checkbox");
+    visible_checkbox.set_checked(visible);
+    visible_checkbox.set_title("Visible");
+    let checkbox_clone = visible_checkbox.clone();
+    let on_click_handler = Closure::wrap(Box::new(move || {
+        let visible = checkbox_clone.checked();
+        update(id, |layer| layer.visible = visible);
+    }) as Box<dyn FnMut()>);
+    visible_checkbox.set_onclick(Some(on_click_handler.as_ref().unchecked_ref()));
+    on_click_handler.forget();
+    row.append_child(&visible_checkbox).unwrap();
+
+    let opacity_slider = new_input("range");
+    opacity_slider.set_min("0");
+    opacity_slider.set_max("1");
+    opacity_slider.set_step("0.05");
+    opacity_slider.set_value(&opacity.to_string());
+    opacity_slider.set_title("Opacity");
+    let slider_clone = opacity_slider.clone();
+    let on_input_handler = Closure::wrap(Box::new(move || {
+        let opacity = slider_clone.value_as_number();
+        update(id, |layer| layer.opacity = opacity);
+    }) as Box<dyn FnMut()>);
+    opacity_slider.set_oninput(Some(on_input_handler.as_ref().unchecked_ref()));
+    on_input_handler.forget();
+    row.append_child(&opacity_slider).unwrap();
+
+    let blend_select = document()
+        .create_element("select")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap();
+    blend_select.set_title("Blend");
+    for option in ALL_BLENDS.iter() {
+        let element =
+            web_sys::HtmlOptionElement::new_with_text_and_value(option.name(), option.name())
+                .unwrap();
+        element.set_selected(*option == blend);
+        blend_select.append_child(&element).unwrap();
+    }
+    let select_clone = blend_select.clone();
+    let on_change_handler = Closure::wrap(Box::new(move || {
+        if let Some(blend) = Blend::of_name(&select_clone.value()) {
+            update(id, |layer| layer.blend = blend);
+        }
+    }) as Box<dyn FnMut()>);
+    blend_select.set_onchange(Some(on_change_handler.as_ref().unchecked_ref()));
+    on_change_handler.forget();
+    row.append_child(&blend_select).unwrap();
+
+    row.append_child(&new_button("⬆️", move || {
+        move_up(id, true);
+        render_panel();
+    }))
+    .unwrap();
+    row.append_child(&new_button("⬇️", move || {
+        move_up(id, false);
+        render_panel();
+    }))
+    .unwrap();
+
+    let universe_clone = Arc::clone(universe);
+    row.append_child(&new_button("✖️", move || {
+        remove(id, &mut universe_clone.lock().unwrap());
+        render_panel();
+    }))
+    .unwrap();
+    row
+}
+
+fn render_panel() {
+    let panel = match document().get_element_by_id(LAYERS_DIV_ID) {
+        Some(panel) => panel,
+        None => return,
+    };
+    let universe = match PANEL_UNIVERSE.with(|universe| universe.borrow().clone()) {
+        Some(universe) => universe,
+        None => return,
+    };
+    panel.set_inner_html("");
+
+    // the top layer is listed first
+    let rows: Vec<_> = LAYERS.with(|layers| {
+        let layers = layers.borrow();
+        layers
+            .layers
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, layer)| {
+                (
+                    layer.id,
+                    index + 1,
+                    index == layers.active,
+                    layer.visible,
+                    layer.opacity,
+                    layer.blend,
+                )
+            })
+            .collect()
+    });
+    for (id, number, active, visible, opacity, blend) in rows {
+        let row = layer_row(&universe, id, number, active, visible, opacity, blend);
+        panel.append_child(&row).unwrap();
+    }
+
+    let universe_clone = Arc::clone(&universe);
+    let add_button = new_button("➕", move || {
+        add(&mut universe_clone.lock().unwrap());
+        render_panel();
+    });
+    add_button.set_title("Add a layer");
+    panel.append_child(&add_button).unwrap();
+}
+
+pub fn layers_panel(universe: &Arc<Mutex<Universe>>) -> Result<(), JsValue> {
+    PANEL_UNIVERSE.with(|panel_universe| *panel_universe.borrow_mut() = Some(Arc::clone(universe)));
+    LAYERS.with(|layers| {
+        let layers = layers.borrow();
+        layers.layers[0].canvas.set_class_name("layer");
+        layers.layers[0].apply_style();
+    });
+
+    let panel = document()
+        .create_element("div")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlDivElement>()
+        .unwrap();
+    panel.set_id(LAYERS_DIV_ID);
+    panel.set_class_name("control");
+    sidebar::section(Group::SIMULATION).append_child(&panel)?;
+    render_panel();
+    Ok(())
+}
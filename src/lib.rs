@@ -6,35 +6,53 @@ use std::sync::{Arc, Mutex};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 
-mod circle;
-use circle::{Circle, CircleConfig, ColorMode, Config, SizeMode, Speed, Status, Universe};
+pub mod action;
+pub mod animation;
+pub mod background;
+mod background_ui;
+pub mod blend;
+pub mod circle;
+mod history;
+mod input;
+mod keymap;
+mod layers;
+mod overlay;
+mod panel;
+pub mod preset;
+mod preset_ui;
+pub mod raster;
+mod recording;
+pub mod registry;
+mod selection;
+pub mod sheet;
+mod sidebar;
+pub mod size;
+mod size_ui;
+mod stepping;
+pub mod stroke;
+pub mod viewport;
+mod viewport_ui;
+use action::{Action, Log};
+use circle::{Circle, Universe};
+use registry::Group;
+use stroke::Stroke;
 
 const ADD_BUTTON_ID: &str = "add-button";
 const APPLE_BUTTON_ID: &str = "apple-button";
-
-#[derive(Copy, Clone)]
-pub enum StrokeColor {
-    BLACK,
-    FILLCOLOR,
-    DARKER,
-}
-
-fn draw_circle(
-    context: &web_sys::CanvasRenderingContext2d,
-    circle: &Circle,
-    stroke_color: StrokeColor,
-) {
-    let color = JsValue::from_str(&circle.color());
-
-    context.set_fill_style(&color);
+const START_STOP_BUTTON_ID: &str = "start-stop-button";
+const SPEED_BUTTON_ID: &str = "speed-button";
+const STEP_BUTTON_ID: &str = "step-button";
+const FREEZE_BUTTON_ID: &str = "freeze-button";
+const SAVE_BUTTON_ID: &str = "save-button";
+const TRASH_BUTTON_ID: &str = "trash-button";
+const UNDO_BUTTON_ID: &str = "undo-button";
+const REDO_BUTTON_ID: &str = "redo-button";
+const LOAD_LOG_INPUT_ID: &str = "load-log-input";
+const RECORD_BUTTON_ID: &str = "record-button";
+const DEFAULT_FILE_NAME: &str = "art";
+
+fn draw_circle(context: &web_sys::CanvasRenderingContext2d, circle: &Circle, stroke: &Stroke) {
     context.begin_path();
-    let stroke_style = match stroke_color {
-        StrokeColor::BLACK => JsValue::from_str("rgb(0,0,0)"),
-        StrokeColor::FILLCOLOR => color,
-        StrokeColor::DARKER => JsValue::from_str(&circle.color.to_slightly_darker_color()),
-    };
-    context.set_stroke_style(&stroke_style);
-
     context
         .arc(
             circle.position.x,
@@ -45,38 +63,47 @@ fn draw_circle(
         )
         .unwrap();
 
-    context.fill();
-    context.stroke();
+    if !stroke.hollow {
+        context.set_fill_style(&JsValue::from_str(&circle.color()));
+        context.fill();
+    }
+    // a line width of 0 is ignored by the canvas rather than drawing nothing
+    if stroke.width > 0.0 {
+        let pattern = stroke
+            .dash
+            .pattern(stroke.width)
+            .into_iter()
+            .map(JsValue::from_f64)
+            .collect::<js_sys::Array>();
+        context.set_line_dash(&pattern).unwrap();
+        context.set_line_width(stroke.width);
+        context.set_stroke_style(&JsValue::from_str(&stroke.color.css(circle)));
+        context.stroke();
+    }
 }
 
 pub fn render(universe: &Universe, canvas: &web_sys::HtmlCanvasElement) {
     let context = context(&canvas);
+    context
+        .set_global_composite_operation(universe.config.blend.composite_operation())
+        .unwrap();
 
     for circle in universe.circles.iter() {
-        if universe.config.bug_checkbox {
-            draw_circle(&context, &circle, StrokeColor::BLACK);
-        } else {
-        };
-        draw_circle(&context, &circle, StrokeColor::FILLCOLOR);
+        draw_circle(&context, &circle, &universe.config.stroke);
     }
 
     for apple in universe.apples.iter() {
-        if universe.config.bug_checkbox {
-            draw_circle(&context, &apple.circle, StrokeColor::BLACK);
-        } else {
-        };
-        draw_circle(&context, &apple.circle, StrokeColor::FILLCOLOR);
+        draw_circle(&context, &apple.circle, &universe.config.stroke);
     }
+    context
+        .set_global_composite_operation("source-over")
+        .unwrap();
 }
 
-pub fn highlight(
-    universe: &Universe,
-    canvas: &web_sys::HtmlCanvasElement,
-    stroke_color: StrokeColor,
-) {
+pub fn highlight(universe: &Universe, canvas: &web_sys::HtmlCanvasElement, stroke: &Stroke) {
     let context = context(&canvas);
     for circle in universe.circles.iter() {
-        draw_circle(&context, &circle, stroke_color);
+        draw_circle(&context, &circle, stroke);
     }
 }
 
@@ -84,6 +111,11 @@ fn window() -> web_sys::Window {
     web_sys::window().expect("no global `window` exists")
 }
 
+// milliseconds, for timing frames
+fn now() -> f64 {
+    window().performance().unwrap().now()
+}
+
 fn document() -> web_sys::Document {
     window()
         .document()
@@ -94,6 +126,11 @@ fn body() -> web_sys::HtmlElement {
     document().body().unwrap()
 }
 
+// none when the browser won't keep anything, like in some private windows
+fn storage() -> Option<web_sys::Storage> {
+    window().local_storage().unwrap_or(None)
+}
+
 fn get_canvas_by_id(id: &str) -> web_sys::HtmlCanvasElement {
     document()
         .get_element_by_id(id)
@@ -103,8 +140,9 @@ fn get_canvas_by_id(id: &str) -> web_sys::HtmlCanvasElement {
         .unwrap()
 }
 
+// the canvas of the layer being drawn on
 fn default_canvas() -> web_sys::HtmlCanvasElement {
-    get_canvas_by_id("canvas")
+    layers::active_canvas()
 }
 
 fn overlay_canvas() -> web_sys::HtmlCanvasElement {
@@ -112,7 +150,10 @@ fn overlay_canvas() -> web_sys::HtmlCanvasElement {
 }
 
 fn all_canvases() -> Vec<web_sys::HtmlCanvasElement> {
-    vec![default_canvas(), overlay_canvas()]
+    let mut canvases = vec![background_ui::canvas()];
+    canvases.extend(layers::canvases());
+    canvases.push(overlay_canvas());
+    canvases
 }
 
 fn context(canvas: &web_sys::HtmlCanvasElement) -> web_sys::CanvasRenderingContext2d {
@@ -130,13 +171,6 @@ fn request_animation_frame(f: &Closure<dyn FnMut()>) {
         .expect("should register `requestAnimationFrame` OK");
 }
 
-fn blank_canvas(canvas: &web_sys::HtmlCanvasElement) {
-    let context = context(&canvas);
-
-    context.set_fill_style(&JsValue::from_str("white"));
-    context.fill_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
-}
-
 fn clear_canvas(canvas: &web_sys::HtmlCanvasElement) {
     let context = context(&canvas);
 
@@ -145,10 +179,8 @@ fn clear_canvas(canvas: &web_sys::HtmlCanvasElement) {
 
 fn clear_board() {
     web_sys::console::log(&js_sys::Array::from(&JsValue::from_str("CLEAR")));
-    for canvas in all_canvases() {
-        clear_canvas(&canvas)
-    }
-    blank_canvas(&default_canvas())
+    clear_canvas(&default_canvas());
+    clear_canvas(&overlay_canvas());
 }
 
 #[derive(Clone)]
@@ -161,6 +193,7 @@ struct SliderConfig {
     of_universe: fn(&Universe) -> f64,
     on_update: fn(&mut Universe, f64) -> (),
     left_label: Option<String>,
+    undoable: bool,
 }
 
 impl SliderConfig {
@@ -224,7 +257,11 @@ impl SliderConfig {
 
             let value = config_clone.get_value();
 
-            (&config_clone.on_update)(&mut slider_universe.lock().unwrap(), value);
+            let mut universe = slider_universe.lock().unwrap();
+            if config_clone.undoable {
+                history::checkpoint_control(&config_clone.id, &universe);
+            }
+            (&config_clone.on_update)(&mut universe, value);
 
             let display = document()
                 .get_element_by_id(&display_id)
@@ -248,7 +285,11 @@ impl SliderConfig {
                 .unwrap()
                 .value_as_number();
 
-            (&display_config_clone.on_update)(&mut display_universe.lock().unwrap(), value);
+            let mut universe = display_universe.lock().unwrap();
+            if display_config_clone.undoable {
+                history::checkpoint_control(&display_config_clone.id, &universe);
+            }
+            (&display_config_clone.on_update)(&mut universe, value);
 
             let slider = document()
                 .get_element_by_id(&slider_id)
@@ -332,6 +373,7 @@ struct ButtonConfig {
     id: String,
     text: ButtonText,
     on_click: fn(&mut Universe) -> (),
+    undoable: bool,
 }
 
 impl ButtonConfig {
@@ -355,6 +397,9 @@ impl ButtonConfig {
                 "You pushed a button!",
             )));
 
+            if self.undoable {
+                history::checkpoint(&universe_clone.lock().unwrap());
+            }
             (self.on_click)(&mut universe_clone.lock().unwrap());
 
             match self.text {
@@ -383,7 +428,9 @@ impl ButtonConfig {
 struct CheckboxConfig {
     id: String,
     text: String,
+    of_universe: fn(&Universe) -> bool,
     on_click: fn(&mut Universe, bool) -> (),
+    undoable: bool,
 }
 
 impl CheckboxConfig {
@@ -396,6 +443,7 @@ impl CheckboxConfig {
 
         checkbox.set_id(&self.id);
         checkbox.set_type("checkbox");
+        checkbox.set_checked((self.of_universe)(&universe.lock().unwrap()));
 
         let div = control_div(&checkbox, &self.id, Some(&self.text));
         let universe_clone = Arc::clone(&universe);
@@ -406,7 +454,11 @@ impl CheckboxConfig {
                 .dyn_into::<web_sys::HtmlInputElement>()
                 .unwrap()
                 .checked();
-            (self.on_click)(&mut universe_clone.lock().unwrap(), is_checked);
+            let mut universe = universe_clone.lock().unwrap();
+            if self.undoable {
+                history::checkpoint(&universe);
+            }
+            (self.on_click)(&mut universe, is_checked);
         }) as Box<dyn FnMut()>);
 
         checkbox.set_onclick(Some(on_click_handler.as_ref().unchecked_ref()));
@@ -415,6 +467,342 @@ impl CheckboxConfig {
     }
 }
 
+// a dropdown of named values, like the blends
+struct SelectConfig {
+    id: String,
+    title: String,
+    left_label: Option<String>,
+    options: Vec<&'static str>,
+    of_universe: fn(&Universe) -> &'static str,
+    on_update: fn(&mut Universe, &str),
+    undoable: bool,
+}
+
+impl SelectConfig {
+    fn new_select(self, universe: &Arc<Mutex<Universe>>) -> web_sys::HtmlDivElement {
+        let select = document()
+            .create_element("select")
+            .unwrap()
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .unwrap();
+
+        select.set_id(&self.id);
+        select.set_title(&self.title);
+        for option in self.options.iter() {
+            select
+                .add_with_html_option_element(
+                    &web_sys::HtmlOptionElement::new_with_text_and_value(option, option).unwrap(),
+                )
+                .unwrap();
+        }
+        select.set_value((self.of_universe)(&universe.lock().unwrap()));
+        CHOICES.with(|choices| {
+            choices
+                .borrow_mut()
+                .push((self.id.clone(), self.of_universe))
+        });
+
+        let div = new_control_div();
+        if let Some(ref text) = self.left_label {
+            div.append_child(&label(&self.id, text)).unwrap();
+        }
+        div.append_child(&select).unwrap();
+
+        let select_clone = select.clone();
+        let universe_clone = Arc::clone(universe);
+        let on_change_handler = Closure::wrap(Box::new(move || {
+            let mut universe = universe_clone.lock().unwrap();
+            if self.undoable {
+                history::checkpoint(&universe);
+            }
+            (self.on_update)(&mut universe, &select_clone.value());
+        }) as Box<dyn FnMut()>);
+
+        select.set_onchange(Some(on_change_handler.as_ref().unchecked_ref()));
+        on_change_handler.forget();
+        div
+    }
+}
+
+// the id of a dropdown or radio group and what it shows
+type Choice = (String, fn(&Universe) -> &'static str);
+
+thread_local! {
+    static CHOICES: RefCell<Vec<Choice>> = RefCell::new(vec![]);
+}
+
+// dropdowns and radio groups only read the universe when they are made
+fn refresh_choices(universe: &Universe) {
+    CHOICES.with(|choices| {
+        for (id, of_universe) in choices.borrow().iter() {
+            let value = of_universe(universe);
+            match document()
+                .get_element_by_id(id)
+                .map(|element| element.dyn_into::<web_sys::HtmlSelectElement>())
+            {
+                Some(Ok(select)) => select.set_value(value),
+                Some(Err(_)) => {
+                    if let Some(radio) = document().get_element_by_id(&format!("{}-{}", id, value))
+                    {
+                        radio
+                            .dyn_into::<web_sys::HtmlInputElement>()
+                            .unwrap()
+                            .set_checked(true);
+                    }
+                }
+                None => {}
+            }
+        }
+    });
+}
+
+// the same as a select but with every value in view, for when there are only a few
+struct RadioConfig {
+    id: String,
+    title: String,
+    options: Vec<&'static str>,
+    of_universe: fn(&Universe) -> &'static str,
+    on_update: fn(&mut Universe, &str),
+    undoable: bool,
+}
+
+impl RadioConfig {
+    fn new_radio_group(self, universe: &Arc<Mutex<Universe>>) -> web_sys::HtmlDivElement {
+        let div = new_control_div();
+        div.set_id(&self.id);
+        div.set_class_name("control radio-group");
+        div.set_title(&self.title);
+
+        let current = (self.of_universe)(&universe.lock().unwrap());
+        CHOICES.with(|choices| {
+            choices
+                .borrow_mut()
+                .push((self.id.clone(), self.of_universe))
+        });
+        let on_update = self.on_update;
+        let undoable = self.undoable;
+        for option in self.options.iter().copied() {
+            let radio_id = format!("{}-{}", self.id, option);
+            let radio = document()
+                .create_element("input")
+                .unwrap()
+                .dyn_into::<web_sys::HtmlInputElement>()
+                .unwrap();
+            radio.set_id(&radio_id);
+            radio.set_type("radio");
+            radio.set_name(&self.id);
+            radio.set_value(option);
+            radio.set_checked(option == current);
+            div.append_child(&radio).unwrap();
+            div.append_child(&label(&radio_id, option)).unwrap();
+
+            let universe_clone = Arc::clone(universe);
+            let on_change_handler = Closure::wrap(Box::new(move || {
+                let mut universe = universe_clone.lock().unwrap();
+                if undoable {
+                    history::checkpoint(&universe);
+                }
+                (on_update)(&mut universe, option);
+            }) as Box<dyn FnMut()>);
+
+            radio.set_onchange(Some(on_change_handler.as_ref().unchecked_ref()));
+            on_change_handler.forget();
+        }
+        div
+    }
+}
+
+struct ColorConfig {
+    id: String,
+    text: String,
+    of_universe: fn(&Universe) -> [u8; 3],
+    on_update: fn(&mut Universe, [u8; 3]),
+    undoable: bool,
+}
+
+impl ColorConfig {
+    fn new_color_picker(self, universe: &Arc<Mutex<Universe>>) -> web_sys::HtmlDivElement {
+        let input = document()
+            .create_element("input")
+            .unwrap()
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+
+        input.set_id(&self.id);
+        input.set_type("color");
+        input.set_value(&background::to_hex((self.of_universe)(
+            &universe.lock().unwrap(),
+        )));
+
+        let div = control_div(&input, &self.id, Some(&self.text));
+        let input_clone = input.clone();
+        let universe_clone = Arc::clone(universe);
+        let on_input_handler = Closure::wrap(Box::new(move || {
+            if let Some(rgb) = background::parse_color(&input_clone.value()) {
+                let mut universe = universe_clone.lock().unwrap();
+                // dragging around the picker is one change, not one per color passed
+                if self.undoable {
+                    history::checkpoint_control(&self.id, &universe);
+                }
+                (self.on_update)(&mut universe, rgb);
+            }
+        }) as Box<dyn FnMut()>);
+
+        input.set_oninput(Some(on_input_handler.as_ref().unchecked_ref()));
+        on_input_handler.forget();
+        div
+    }
+}
+
+// updates once the text is done, on enter or when it loses focus
+struct TextConfig {
+    id: String,
+    text: String,
+    placeholder: String,
+    of_universe: fn(&Universe) -> String,
+    on_update: fn(&mut Universe, &str),
+    undoable: bool,
+}
+
+impl TextConfig {
+    fn new_text_input(self, universe: &Arc<Mutex<Universe>>) -> web_sys::HtmlDivElement {
+        let input = document()
+            .create_element("input")
+            .unwrap()
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+
+        input.set_id(&self.id);
+        input.set_type("text");
+        input.set_placeholder(&self.placeholder);
+        input.set_value(&(self.of_universe)(&universe.lock().unwrap()));
+
+        let div = control_div(&input, &self.id, Some(&self.text));
+        let input_clone = input.clone();
+        let universe_clone = Arc::clone(universe);
+        let on_change_handler = Closure::wrap(Box::new(move || {
+            let mut universe = universe_clone.lock().unwrap();
+            if self.undoable {
+                history::checkpoint(&universe);
+            }
+            (self.on_update)(&mut universe, &input_clone.value());
+        }) as Box<dyn FnMut()>);
+
+        input.set_onchange(Some(on_change_handler.as_ref().unchecked_ref()));
+        on_change_handler.forget();
+        div
+    }
+}
+
+thread_local! {
+    static FILE_NAME: RefCell<String> = RefCell::new(String::from(DEFAULT_FILE_NAME));
+}
+
+fn file_name(_universe: &Universe) -> String {
+    FILE_NAME.with(|name| name.borrow().clone())
+}
+
+fn set_file_name(_universe: &mut Universe, value: &str) {
+    FILE_NAME.with(|name| *name.borrow_mut() = String::from(value.trim()));
+}
+
+// what saved images, logs and recordings are called, like art.png or art-log.txt
+fn named(suffix: &str) -> String {
+    FILE_NAME.with(|name| match name.borrow().as_str() {
+        "" => format!("{}{}", DEFAULT_FILE_NAME, suffix),
+        name => format!("{}{}", name, suffix),
+    })
+}
+
+fn download(href: &str, filename: &str) {
+    let anchor = document()
+        .create_element("a")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .unwrap();
+
+    anchor.set_href(href);
+    anchor.set_download(filename);
+    anchor.click();
+}
+
+fn download_bytes(bytes: &[u8], mime_type: &str, filename: &str) {
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_(mime_type);
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(
+        &js_sys::Array::of1(&js_sys::Uint8Array::from(bytes)),
+        &options,
+    )
+    .unwrap();
+    download(
+        &web_sys::Url::create_object_url_with_blob(&blob).unwrap(),
+        filename,
+    );
+}
+
+fn start_replay(universe: &mut Universe, log: &Log) {
+    *universe = Universe::replay(log);
+    update_canvas_size(log.height, log.width);
+    universe.request_clear();
+    panel::refresh(universe);
+}
+
+fn load_log_input(universe: &Arc<Mutex<Universe>>) -> web_sys::HtmlDivElement {
+    let input = document()
+        .create_element("input")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+
+    input.set_id(LOAD_LOG_INPUT_ID);
+    input.set_type("file");
+    input.set_accept(".txt");
+
+    let div = control_div(&input, LOAD_LOG_INPUT_ID, Some("📂"));
+    let universe_clone = Arc::clone(universe);
+    let on_change_handler = Closure::wrap(Box::new(move || {
+        let file = document()
+            .get_element_by_id(LOAD_LOG_INPUT_ID)
+            .unwrap()
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .files()
+            .and_then(|files| files.get(0));
+        let file = match file {
+            Some(file) => file,
+            None => return,
+        };
+
+        let reader = web_sys::FileReader::new().unwrap();
+        let reader_clone = reader.clone();
+        let universe_clone = Arc::clone(&universe_clone);
+        let on_load_handler = Closure::wrap(Box::new(move || {
+            let text = reader_clone
+                .result()
+                .unwrap()
+                .as_string()
+                .unwrap_or_default();
+            match text.parse::<Log>() {
+                Ok(log) => {
+                    let mut universe = universe_clone.lock().unwrap();
+                    history::checkpoint(&universe);
+                    start_replay(&mut universe, &log);
+                }
+                Err(error) => web_sys::console::log(&js_sys::Array::from(&JsValue::from_str(
+                    &format!("Couldn't read that log, {}", error),
+                ))),
+            }
+        }) as Box<dyn FnMut()>);
+        reader.set_onload(Some(on_load_handler.as_ref().unchecked_ref()));
+        on_load_handler.forget();
+        reader.read_as_text(&file).unwrap();
+    }) as Box<dyn FnMut()>);
+
+    input.set_onchange(Some(on_change_handler.as_ref().unchecked_ref()));
+    on_change_handler.forget();
+    div
+}
+
 fn indicate_next_step(no_circles: bool) {
     let class_name = if no_circles { "highlight" } else { "" };
     for button_id in vec![ADD_BUTTON_ID, APPLE_BUTTON_ID] {
@@ -425,11 +813,42 @@ fn indicate_next_step(no_circles: bool) {
     }
 }
 
+// a copy of what is on a canvas, for putting back after something wipes it
+fn copy_canvas(canvas: &web_sys::HtmlCanvasElement) -> web_sys::HtmlCanvasElement {
+    let copy = document()
+        .create_element("canvas")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+    copy.set_width(canvas.width());
+    copy.set_height(canvas.height());
+    context(&copy)
+        .draw_image_with_html_canvas_element(canvas, 0.0, 0.0)
+        .unwrap();
+    copy
+}
+
+// changing the size of a canvas wipes it, so what was drawn is put back in the top left
 fn update_canvas_size(height: f64, width: f64) {
     for canvas in all_canvases() {
+        if canvas.width() == width as u32 && canvas.height() == height as u32 {
+            continue;
+        }
+        let copy = if canvas.width() > 0 && canvas.height() > 0 {
+            Some(copy_canvas(&canvas))
+        } else {
+            None
+        };
         canvas.set_height(height as u32);
         canvas.set_width(width as u32);
+        if let Some(copy) = copy {
+            context(&canvas)
+                .draw_image_with_html_canvas_element(&copy, 0.0, 0.0)
+                .unwrap();
+        }
     }
+    size_ui::fit_to_window();
+    background_ui::draw();
 }
 
 // Called when the wasm module is instantiated
@@ -438,249 +857,335 @@ pub fn main() -> Result<(), JsValue> {
     let width = body().client_width();
     let height = body().client_height();
     update_canvas_size(height.into(), width.into());
-    let universe = Arc::new(Mutex::new(Universe {
-        config: Config {
-            status: Status::RUNNING,
-            speed: Speed::NORMAL,
-            bug_checkbox: false,
-            radius: 10.,
-            apple_steps: 1000,
-            initial_height: height as f64,
-            initial_width: width as f64,
-            color_mode: ColorMode::RGB,
-            size_mode: SizeMode::NORMAL,
-        },
-        circle_config: CircleConfig {
-            height: height as f64,
-            width: width as f64,
-            max_position_delta: 6.3,
-            max_color_delta: 5,
-        },
-        circles: vec![],
-        apples: vec![],
-    }));
-
-    let distance_slider_id = "distance-slider";
-    let distance_slider_config = SliderConfig {
-        id: String::from(distance_slider_id),
-        title: String::from("Movement Speed"),
-
-        left_label: Some(String::from("↔")),
-        min: 0.0,
-        max: 100.0,
-        step: 1.0,
-        of_universe: (move |universe| universe.circle_config.max_position_delta),
-        on_update: (move |universe, value| universe.circle_config.max_position_delta = value),
-    };
-
-    let distance_slider_div = SliderConfig::create_slider(&distance_slider_config, &universe);
-
-    let size_mode_button_id = "size-mode-button";
-    let size_mode_button_config = ButtonConfig {
-        id: String::from(size_mode_button_id),
-
-        text: ButtonText::DYNAMIC(move |universe| universe.config.size_mode.to_button_display()),
-        on_click: (move |universe| {
-            universe.toggle_size_mode();
-            update_canvas_size(universe.circle_config.height, universe.circle_config.width)
-        }),
-    };
-
-    let size_mode_button = ButtonConfig::new_button(size_mode_button_config, &universe);
-
-    let color_slider_id = "color-slider";
-    let color_slider_config = SliderConfig {
-        id: String::from(color_slider_id),
-        title: String::from("Color Speed"),
-        left_label: Some(String::from("🌈")),
-        min: 0.0,
-        max: 50.0,
-        step: 1.0,
-        of_universe: (move |universe| universe.circle_config.max_color_delta as f64),
-        on_update: (move |universe, value| universe.circle_config.max_color_delta = value as u8),
-    };
-
-    let color_slider_div = SliderConfig::create_slider(&color_slider_config, &universe);
-
-    let radius_slider_id = "radius-slider";
-    let radius_slider_config = SliderConfig {
-        id: String::from(radius_slider_id),
-        title: String::from("Size"),
-        left_label: None,
-        min: 1.0,
-        max: 100.0,
-        step: 1.0,
-        of_universe: (move |universe| universe.config.radius),
-        on_update: (move |universe, value| universe.config.radius = value),
+    let universe = Arc::new(Mutex::new(Universe::new(
+        width as f64,
+        height as f64,
+        js_sys::Date::now() as u64,
+    )));
+    panel::load_link(&mut universe.lock().unwrap());
+
+    let size_div = size_ui::size_div(&universe);
+    let follow_window_checkbox_config = CheckboxConfig {
+        id: String::from("follow-window-checkbox"),
+        text: String::from("🪟"),
+        of_universe: size_ui::follow_window,
+        on_click: size_ui::set_follow_window,
+        undoable: false,
     };
+    let follow_window_checkbox = follow_window_checkbox_config.new_checkbox(&universe);
+    while let Some(child) = follow_window_checkbox.first_child() {
+        size_div.append_child(&child)?;
+    }
 
     let add_button_config = ButtonConfig {
         id: String::from(ADD_BUTTON_ID),
         text: ButtonText::STATIC(String::from("+")),
         on_click: (move |universe| {
-            universe.add_circle();
+            universe.perform(Action::CIRCLE(None));
 
             document()
                 .get_element_by_id(ADD_BUTTON_ID)
                 .unwrap()
                 .set_class_name("");
         }),
+        undoable: true,
     };
     let add_button = add_button_config.new_button(&universe);
 
-    let new_circle_div = SliderConfig::create_slider(&radius_slider_config, &universe);
-
-    new_circle_div.append_child(&add_button)?;
+    let brush_div = panel::group_div(Group::BRUSH, &universe);
+    brush_div.insert_before(&add_button, brush_div.first_child().as_ref())?;
 
     let freeze_button_config = ButtonConfig {
-        id: String::from("freeze-button"),
+        id: String::from(FREEZE_BUTTON_ID),
         text: ButtonText::STATIC(String::from("🧊")),
 
         on_click: (move |universe| {
-            universe.circles.clear();
+            universe.perform(Action::FREEZE);
         }),
+        undoable: true,
     };
 
     let freeze_button = ButtonConfig::new_button(freeze_button_config, &universe);
 
-    let apple_steps_slider_config = SliderConfig {
-        id: String::from("apple-steps-slider"),
-        title: String::from("Steps"),
-        left_label: Some(String::from("👣")),
-        min: 0.0,
-        max: 10000.0,
-        step: 100.0,
-        of_universe: (move |universe| universe.config.apple_steps as f64),
-        on_update: (move |universe, value| universe.config.apple_steps = value as u32),
-    };
-
     let apple_button_config = ButtonConfig {
-        id: String::from("apple-button"),
+        id: String::from(APPLE_BUTTON_ID),
         text: ButtonText::STATIC(String::from("🍏")),
 
         on_click: (move |universe| {
-            universe.add_apple();
+            universe.perform(Action::APPLE(None));
         }),
+        undoable: true,
     };
 
-    let new_apple_div = SliderConfig::create_slider(&apple_steps_slider_config, &universe);
-
+    let apples_div = panel::group_div(Group::APPLES, &universe);
     let apple_button = ButtonConfig::new_button(apple_button_config, &universe);
-    new_apple_div.append_child(&apple_button)?;
+    apples_div.insert_before(&apple_button, apples_div.first_child().as_ref())?;
 
-    let start_stop_button_id = "start-stop-button";
     let start_stop_button_config = ButtonConfig {
-        id: String::from(start_stop_button_id),
+        id: String::from(START_STOP_BUTTON_ID),
         text: ButtonText::DYNAMIC(move |universe| universe.config.status.to_button_display()),
         on_click: move |universe| {
-            universe.config.status.toggle();
+            universe.perform(Action::PAUSE);
         },
+        undoable: false,
     };
     let start_stop_button = start_stop_button_config.new_button(&universe);
 
-    let speed_button_id = "speed-button";
     let speed_button_config = ButtonConfig {
-        id: String::from(speed_button_id),
+        id: String::from(SPEED_BUTTON_ID),
         text: ButtonText::DYNAMIC(move |universe| universe.config.speed.to_button_display()),
         on_click: (move |universe| {
-            universe.config.speed.toggle();
+            universe.perform(Action::SPEED);
         }),
+        undoable: false,
     };
     let speed_button = speed_button_config.new_button(&universe);
 
-    let color_mode_button_id = "color-mode-button";
-    let color_mode_button_config = ButtonConfig {
-        id: String::from(color_mode_button_id),
-        text: ButtonText::DYNAMIC(move |universe| universe.config.color_mode.to_button_display()),
-        on_click: (move |universe| {
-            universe.config.color_mode.toggle();
-        }),
+    let simulation_div = panel::group_div(Group::SIMULATION, &universe);
+    simulation_div.insert_before(&speed_button, simulation_div.first_child().as_ref())?;
+
+    let step_button_config = ButtonConfig {
+        id: String::from(STEP_BUTTON_ID),
+        text: ButtonText::STATIC(String::from("⏯️")),
+        on_click: stepping::step_once,
+        undoable: false,
+    };
+    let step_button = step_button_config.new_button(&universe);
+
+    let run_ticks_slider_config = SliderConfig {
+        id: String::from("run-ticks-slider"),
+        title: String::from("Ticks to Run"),
+        left_label: None,
+        min: 1.0,
+        max: 1000.0,
+        step: 1.0,
+        of_universe: stepping::ticks,
+        on_update: stepping::set_ticks,
+        undoable: false,
+    };
+    let stepping_div = SliderConfig::create_slider(&run_ticks_slider_config, &universe);
+    stepping_div.insert_before(&step_button, stepping_div.first_child().as_ref())?;
+
+    let run_ticks_button_config = ButtonConfig {
+        id: String::from("run-ticks-button"),
+        text: ButtonText::STATIC(String::from("⏩")),
+        on_click: stepping::run_ticks,
+        undoable: false,
     };
-    // USE OR DELETE
-    let _color_mode_button = color_mode_button_config.new_button(&universe);
+    stepping_div.append_child(&run_ticks_button_config.new_button(&universe))?;
+
+    let run_apples_button_config = ButtonConfig {
+        id: String::from("run-apples-button"),
+        text: ButtonText::STATIC(String::from("🍏")),
+        on_click: stepping::run_apples,
+        undoable: false,
+    };
+    stepping_div.append_child(&run_apples_button_config.new_button(&universe))?;
 
     let trash_button_config = ButtonConfig {
-        id: String::from("trash-button"),
+        id: String::from(TRASH_BUTTON_ID),
         text: ButtonText::STATIC(String::from("🗑️")),
         on_click: (move |universe| {
-            universe.circles.clear();
-            universe.apples.clear();
-            clear_board();
+            universe.perform(Action::TRASH);
             document()
                 .get_element_by_id(ADD_BUTTON_ID)
                 .unwrap()
                 .set_class_name("highlight");
         }),
+        undoable: true,
     };
     let trash_button = trash_button_config.new_button(&universe);
 
     let save_button_config = ButtonConfig {
-        id: String::from("save-button"),
+        id: String::from(SAVE_BUTTON_ID),
         text: ButtonText::STATIC(String::from("💾")),
         on_click: (move |_universe| {
-            let image = default_canvas().to_data_url().unwrap();
-
-            let anchor = document()
-                .create_element("a")
-                .unwrap()
-                .dyn_into::<web_sys::HtmlAnchorElement>()
-                .unwrap();
-
-            anchor.set_href(&image);
-            anchor.set_download("art.png");
-            anchor.click();
+            let image = layers::flatten().to_data_url().unwrap();
+            download(&image, &named(".png"));
         }),
+        undoable: false,
     };
     let save_button = save_button_config.new_button(&universe);
 
-    let bug_checkbox_config = CheckboxConfig {
-        id: String::from("bug-checkbox"),
-        text: String::from("🐛"),
-        on_click: (move |universe, value| {
-            universe.config.bug_checkbox = value;
+    let log_button_config = ButtonConfig {
+        id: String::from("log-button"),
+        text: ButtonText::STATIC(String::from("📜")),
+        on_click: (move |universe| {
+            let log = universe.to_log().to_string();
+            let href = format!(
+                "data:text/plain;charset=utf-8,{}",
+                js_sys::encode_uri_component(&log)
+            );
+            download(&href, &named("-log.txt"));
         }),
+        undoable: false,
+    };
+    let log_button = log_button_config.new_button(&universe);
+
+    let replay_button_config = ButtonConfig {
+        id: String::from("replay-button"),
+        text: ButtonText::STATIC(String::from("🔁")),
+        on_click: (move |universe| {
+            let log = universe.to_log();
+            start_replay(universe, &log);
+        }),
+        undoable: true,
+    };
+    let replay_button = replay_button_config.new_button(&universe);
+
+    let link_button_config = ButtonConfig {
+        id: String::from("link-button"),
+        text: ButtonText::STATIC(String::from("🔗")),
+        on_click: panel::update_link,
+        undoable: false,
+    };
+    let link_button = link_button_config.new_button(&universe);
+    link_button.set_title("Put the settings in the address bar");
+    let load_log_div = load_log_input(&universe);
+
+    let file_name_config = TextConfig {
+        id: String::from("file-name-input"),
+        text: String::from("🏷️"),
+        placeholder: String::from(DEFAULT_FILE_NAME),
+        of_universe: file_name,
+        on_update: set_file_name,
+        undoable: false,
+    };
+    let file_name_div = file_name_config.new_text_input(&universe);
+
+    let undo_button_config = ButtonConfig {
+        id: String::from(UNDO_BUTTON_ID),
+        text: ButtonText::STATIC(String::from("↩️")),
+        on_click: history::undo,
+        undoable: false,
     };
-    let bug_checkbox = bug_checkbox_config.new_checkbox(&universe);
-
-    body().append_child(&start_stop_button)?;
-    body().append_child(&speed_button)?;
-    body().append_child(&freeze_button)?;
-    body().append_child(&save_button)?;
-    body().append_child(&trash_button)?;
-    body().append_child(&new_circle_div)?;
-    body().append_child(&new_apple_div)?;
-    body().append_child(&bug_checkbox)?;
-    body().append_child(&distance_slider_div)?;
-    body().append_child(&color_slider_div)?;
-    // body().append_child(&size_mode_button)?;
-    // body().append_child(&color_mode_button)?;
-
-    universe.lock().unwrap().add_circle();
-    universe.lock().unwrap().add_circle();
+    let undo_button = undo_button_config.new_button(&universe);
+
+    let redo_button_config = ButtonConfig {
+        id: String::from(REDO_BUTTON_ID),
+        text: ButtonText::STATIC(String::from("↪️")),
+        on_click: history::redo,
+        undoable: false,
+    };
+    let redo_button = redo_button_config.new_button(&universe);
+
+    let record_button_config = ButtonConfig {
+        id: String::from(RECORD_BUTTON_ID),
+        text: ButtonText::DYNAMIC(recording::button_display),
+        on_click: recording::toggle,
+        undoable: false,
+    };
+    let record_button = record_button_config.new_button(&universe);
+
+    let recording_div = panel::group_div(Group::EXPORT, &universe);
+    recording_div.insert_before(&record_button, recording_div.first_child().as_ref())?;
+
+    let gif_button_config = ButtonConfig {
+        id: String::from("gif-button"),
+        text: ButtonText::STATIC(String::from("🎬")),
+        on_click: recording::save_gif,
+        undoable: false,
+    };
+    recording_div.append_child(&gif_button_config.new_button(&universe))?;
+
+    let frames_button_config = ButtonConfig {
+        id: String::from("frames-button"),
+        text: ButtonText::STATIC(String::from("🖼️")),
+        on_click: recording::save_frames,
+        undoable: false,
+    };
+    recording_div.append_child(&frames_button_config.new_button(&universe))?;
+
+    let simulation = sidebar::section(Group::SIMULATION);
+    simulation.append_child(&start_stop_button)?;
+    simulation.append_child(&freeze_button)?;
+    simulation.append_child(&trash_button)?;
+    simulation.append_child(&undo_button)?;
+    simulation.append_child(&redo_button)?;
+    simulation.append_child(&simulation_div)?;
+    simulation.append_child(&stepping_div)?;
+    simulation.append_child(&size_div)?;
+
+    let brush = sidebar::section(Group::BRUSH);
+    brush.append_child(&brush_div)?;
+
+    let color = sidebar::section(Group::COLOR);
+    color.append_child(&panel::group_div(Group::COLOR, &universe))?;
+
+    sidebar::section(Group::APPLES).append_child(&apples_div)?;
+
+    let export = sidebar::section(Group::EXPORT);
+    export.append_child(&preset_ui::presets_div(&universe))?;
+    export.append_child(&file_name_div)?;
+    export.append_child(&save_button)?;
+    export.append_child(&log_button)?;
+    export.append_child(&replay_button)?;
+    export.append_child(&link_button)?;
+    export.append_child(&load_log_div)?;
+    export.append_child(&recording_div)?;
+
+    layers::layers_panel(&universe)?;
+    selection::circle_panel(&universe)?;
+    input::listen_for_pointer(&universe);
+    viewport_ui::listen_for_wheel();
+    size_ui::listen_for_resize();
+    keymap::listen_for_keys(&universe)?;
+
+    universe.lock().unwrap().perform(Action::CIRCLE(None));
+    universe.lock().unwrap().perform(Action::CIRCLE(None));
 
     let main_loop = Rc::new(RefCell::new(None));
     let main_loop_copy = main_loop.clone();
 
     clear_board();
     *main_loop_copy.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+        size_ui::handle_resize(&mut universe.lock().unwrap());
         let steps = universe.lock().unwrap().steps();
 
         let overlay_canvas = overlay_canvas();
         let default_canvas = default_canvas();
 
         let mut universe = universe.lock().unwrap();
+        layers::for_each_inactive(|universe, canvas| {
+            let started = now();
+            let steps = universe.steps();
+            for _ in 0..steps {
+                universe.tick();
+                if universe.take_clear_request() {
+                    clear_canvas(canvas);
+                }
+                // the canvases were all resized along with the active layer's
+                universe.take_resize_request();
+                render(universe, canvas);
+            }
+            universe.timed(steps, now() - started);
+        });
+        let started = now();
+        let replaying = universe.is_replaying();
         for _ in 0..steps {
             universe.tick();
+            if universe.take_clear_request() {
+                clear_board();
+            }
+            if universe.take_resize_request() {
+                update_canvas_size(universe.circle_config.height, universe.circle_config.width);
+            }
             render(&universe, &default_canvas);
+            recording::capture(&universe);
+        }
+        universe.timed(steps, now() - started);
+        recording::flush();
+        // the log changes settings as it goes, and the controls should show it
+        if replaying {
+            panel::refresh(&universe);
         }
 
         clear_canvas(&overlay_canvas);
-        match &universe.config.status {
-            Status::RUNNING => {
-                highlight(&universe, &overlay_canvas, StrokeColor::DARKER);
-            }
-            Status::PAUSED => {}
-        }
+        overlay::draw(&universe, &overlay_canvas);
+        stepping::refresh(&universe);
+        recording::refresh(&universe);
+        size_ui::refresh(&universe);
+        viewport_ui::draw_minimap();
 
         request_animation_frame(main_loop.borrow().as_ref().unwrap());
 
@@ -0,0 +1,711 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use art::action::{Action, Log, Parameter};
+use art::animation::Animation;
+use art::background::{parse_color, to_hex, Background, Fill};
+use art::blend::Blend;
+use art::circle::{ColorMode, Universe};
+use art::preset;
+use art::raster::{self, Raster, Surface, Svg};
+use art::registry;
+use art::sheet::{self, Cell};
+use art::size::PaperSize;
+use art::stroke::{Dash, Stroke, StrokeColor};
+
+const USAGE: &str = "usage: art [options]
+
+Draws a piece without a browser and saves it as a png or svg.
+
+options (each can also be a `name = value` line in a --config file):
+  --config FILE                read options from FILE, later flags win
+  --circles N                  circles to start with (2)
+  --apples N                   apples to start with (0)
+  --radius R                   circle radius (10)
+  --max-position-delta D       how far a circle can move each tick (6.3)
+  --max-color-delta C          how far a color can change each tick (5)
+  --apple-steps N              how long an apple lives (1000)
+  --color-mode rgb|hsl         (rgb)
+  --blend MODE                 how circles mix with what's under them: normal, multiply,
+                               screen, overlay, difference or lighter (normal)
+  --stroke-width W             outline width, 0 for none (1)
+  --stroke-color COLOR         black, fill, darker, lighter, complementary or palette (fill)
+  --dash solid|dashed|dotted|dashdot
+                               (solid)
+  --hollow                     only draw the outlines
+  --bug                        the same as --stroke-color black
+  --background COLOR           #rrggbb, or transparent (#ffffff)
+  --gradient solid|linear|radial
+                               fade the background from top to bottom or middle to edge (solid)
+  --background-to COLOR        the color the gradient fades to (#ffffff)
+  --grain G                    paper texture, from 0 for none to 1 (0)
+  --steps N                    ticks to run (1000, or the log's ticks with --replay)
+  --seed N                     random seed (the current time)
+  --width W --height H         canvas size (1000 x 1000)
+  --preset NAME                a4 or a3 at 300 dpi, square, 16:9 or phone, instead of a size
+  --look NAME                  start from a preset: watercolor, confetti, ink or a .json file
+                               saved from the page or with --save-look; other options win
+  --save-look FILE             save the settings as a preset .json file, named after FILE
+  --scale S                    draw S times bigger than the canvas size (1)
+  --replay FILE                redo an action log saved from the page, which decides the
+                               size, seed and settings, so options for those are refused
+  --variants N                 draw N pieces with seeds seed, seed + 1, ... (1)
+  --output FILE                .png, .svg or an animated .gif (art.png)
+  --frame-every N              also save every Nth tick as a frame, as FILE-0001.png, ...
+                               or as the frames of the gif (10 for a gif)
+  --fps F                      frames per second of the gif (20)
+  --log FILE                   also save the action log, for --replay, numbered like
+                               the output with --variants
+  --sweep NAME=FROM:TO:COUNT   draw a contact sheet with COUNT values of NAME, one of
+                               radius, max-position-delta, max-color-delta or apple-steps;
+                               a second --sweep adds rows";
+
+// options that set a parameter of the universe
+fn parameter_option(name: &str) -> Option<Parameter> {
+    match name {
+        "radius" => Some(Parameter::RADIUS),
+        "max-position-delta" => Some(Parameter::DISTANCE),
+        "max-color-delta" => Some(Parameter::COLOR),
+        "apple-steps" => Some(Parameter::STEPS),
+        _ => None,
+    }
+}
+
+// what a parameter can't be, found before anything is drawn
+fn check_parameter(name: &str, parameter: Parameter, value: f64) -> Result<f64, String> {
+    match parameter {
+        Parameter::STEPS if value < 1.0 => Err(format!("--{} is at least 1, not {}", name, value)),
+        _ => Ok(value),
+    }
+}
+
+#[derive(Clone)]
+struct Sweep {
+    name: String,
+    parameter: Parameter,
+    values: Vec<f64>,
+}
+
+impl FromStr for Sweep {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let error = || format!("--sweep is NAME=FROM:TO:COUNT, not {:?}", text);
+        let (name, range) = text.split_once('=').ok_or_else(error)?;
+        let parameter = parameter_option(name).ok_or_else(|| format!("can't sweep {}", name))?;
+        let mut range = range.split(':');
+        let (from, to, count): (f64, f64, usize) = match (range.next(), range.next(), range.next())
+        {
+            (Some(from), Some(to), Some(count)) => (
+                from.parse().map_err(|_| error())?,
+                to.parse().map_err(|_| error())?,
+                count.parse().map_err(|_| error())?,
+            ),
+            _ => return Err(error()),
+        };
+        if count < 1 {
+            return Err(format!(
+                "--sweep needs a COUNT of at least 1, not {}",
+                count
+            ));
+        }
+        let values = (0..count)
+            .map(|i| match count {
+                1 => from,
+                _ => from + (to - from) * i as f64 / (count - 1) as f64,
+            })
+            .map(|value| check_parameter(name, parameter, value))
+            .collect::<Result<_, _>>()?;
+        Ok(Sweep {
+            name: String::from(name),
+            parameter,
+            values,
+        })
+    }
+}
+
+// short enough to fit under a picture
+fn label(value: f64) -> String {
+    let text = format!("{:.3}", value);
+    String::from(text.trim_end_matches('0').trim_end_matches('.'))
+}
+
+// the parts of the stroke that were given, the rest comes from --look or the default
+#[derive(Default)]
+struct StrokeOptions {
+    width: Option<f64>,
+    color: Option<StrokeColor>,
+    dash: Option<Dash>,
+    hollow: Option<bool>,
+}
+
+impl StrokeOptions {
+    fn on(&self, stroke: Stroke) -> Stroke {
+        Stroke {
+            width: self.width.unwrap_or(stroke.width),
+            color: self.color.unwrap_or(stroke.color),
+            dash: self.dash.unwrap_or(stroke.dash),
+            hollow: self.hollow.unwrap_or(stroke.hollow),
+        }
+    }
+}
+
+struct Settings {
+    circles: u32,
+    apples: u32,
+    parameters: Vec<(Parameter, f64)>,
+    look: Option<preset::Preset>,
+    // the page's settings from --look that have an option of the same name
+    look_options: Vec<(String, String)>,
+    save_look: Option<PathBuf>,
+    // none when not given, so they don't undo what --look set
+    color_mode: Option<ColorMode>,
+    blend: Option<Blend>,
+    background: Option<Background>,
+    stroke: StrokeOptions,
+    steps: Option<u64>,
+    seed: u64,
+    width: f64,
+    height: f64,
+    scale: f64,
+    replay: Option<Log>,
+    variants: u32,
+    output: PathBuf,
+    log: Option<PathBuf>,
+    frame_every: Option<u64>,
+    frames_per_second: f64,
+    sweeps: Vec<Sweep>,
+    // the names of the options that were given, in order
+    given: Vec<String>,
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("--{} doesn't understand {:?}", name, value))
+}
+
+fn parse_color_option(name: &str, value: &str) -> Result<[u8; 3], String> {
+    parse_color(value).ok_or_else(|| format!("--{} is a color like #ff8800, not {:?}", name, value))
+}
+
+const FLAGS: [&str; 2] = ["bug", "hollow"];
+
+// the page's settings that a look can carry to the command line
+const LOOK_OPTIONS: [&str; 4] = ["gradient", "background", "background-to", "grain"];
+
+fn is_page(name: &str) -> bool {
+    registry::tunable(name).map_or(false, |tunable| tunable.page)
+}
+
+// options that change how the universe starts, which a replayed log already decides
+const NOT_WITH_REPLAY: [&str; 20] = [
+    "circles",
+    "apples",
+    "radius",
+    "max-position-delta",
+    "max-color-delta",
+    "apple-steps",
+    "color-mode",
+    "blend",
+    "stroke-width",
+    "stroke-color",
+    "dash",
+    "hollow",
+    "bug",
+    "seed",
+    "width",
+    "height",
+    "preset",
+    "look",
+    "sweep",
+    "variants",
+];
+
+impl Settings {
+    fn new() -> Self {
+        Settings {
+            circles: 2,
+            apples: 0,
+            parameters: vec![],
+            look: None,
+            look_options: vec![],
+            save_look: None,
+            color_mode: None,
+            blend: None,
+            background: Some(Background::white()),
+            stroke: StrokeOptions::default(),
+            steps: None,
+            seed: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_millis() as u64),
+            width: 1000.0,
+            height: 1000.0,
+            scale: 1.0,
+            replay: None,
+            variants: 1,
+            output: PathBuf::from("art.png"),
+            log: None,
+            frame_every: None,
+            frames_per_second: 20.0,
+            sweeps: vec![],
+            given: vec![],
+        }
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        self.given.push(String::from(name));
+        match name {
+            "config" => self.read_config(Path::new(value))?,
+            "circles" => self.circles = parse(name, value)?,
+            "apples" => self.apples = parse(name, value)?,
+            name if parameter_option(name).is_some() => {
+                let parameter = parameter_option(name).unwrap();
+                let value = check_parameter(name, parameter, parse(name, value)?)?;
+                self.parameters.push((parameter, value))
+            }
+            "color-mode" => {
+                self.color_mode = Some(
+                    ColorMode::of_name(value)
+                        .ok_or_else(|| format!("--color-mode is rgb or hsl, not {:?}", value))?,
+                )
+            }
+            "blend" => {
+                self.blend = Some(
+                    Blend::of_name(value)
+                        .ok_or_else(|| format!("--blend doesn't know {:?}", value))?,
+                )
+            }
+            "background" if value == "transparent" => self.background = None,
+            "background" => self.background().color = parse_color_option(name, value)?,
+            "background-to" => self.background().to_color = parse_color_option(name, value)?,
+            "gradient" => {
+                self.background().fill = Fill::of_name(value)
+                    .ok_or_else(|| format!("--gradient doesn't know {:?}", value))?
+            }
+            "grain" => self.background().grain = parse(name, value)?,
+            "stroke-width" => self.stroke.width = Some(parse(name, value)?),
+            "stroke-color" => {
+                self.stroke.color = Some(
+                    StrokeColor::of_name(value)
+                        .ok_or_else(|| format!("--stroke-color doesn't know {:?}", value))?,
+                )
+            }
+            "dash" => {
+                self.stroke.dash = Some(
+                    Dash::of_name(value)
+                        .ok_or_else(|| format!("--dash doesn't know {:?}", value))?,
+                )
+            }
+            "hollow" => self.stroke.hollow = Some(value.is_empty() || parse(name, value)?),
+            "bug" if value.is_empty() || parse(name, value)? => {
+                self.stroke.color = Some(StrokeColor::BLACK)
+            }
+            "bug" => {}
+            "steps" => self.steps = Some(parse(name, value)?),
+            "seed" => self.seed = parse(name, value)?,
+            "width" => self.width = parse(name, value)?,
+            "height" => self.height = parse(name, value)?,
+            "preset" => {
+                let paper = PaperSize::of_name(value)
+                    .filter(|paper| *paper != PaperSize::WINDOW)
+                    .ok_or_else(|| format!("--preset doesn't know {:?}", value))?;
+                let (width, height) = paper.size(self.width, self.height);
+                self.width = width;
+                self.height = height;
+            }
+            "look" => {
+                let mut look = if value.ends_with(".json") {
+                    let text = fs::read_to_string(value).map_err(|error| error.to_string())?;
+                    preset::Preset::of_json(&text)
+                        .map_err(|error| format!("{}: {}", value, error))?
+                } else {
+                    preset::built_in_named(value)
+                        .ok_or_else(|| format!("--look doesn't know {:?}", value))?
+                };
+                // the page keeps these outside the universe, some of them have options here
+                let (page, values) = look.values.into_iter().partition(|(name, _)| is_page(name));
+                look.values = values;
+                self.look_options = page;
+                self.look_options
+                    .retain(|(name, _)| LOOK_OPTIONS.contains(&name.as_str()));
+                // so a bad value is found now, not once per variant
+                look.apply(&mut Universe::new(self.width, self.height, 0))
+                    .map_err(|error| format!("{}: {}", value, error))?;
+                self.look = Some(look);
+            }
+            "save-look" => self.save_look = Some(PathBuf::from(value)),
+            "scale" => self.scale = parse(name, value)?,
+            "replay" => {
+                let text = fs::read_to_string(value).map_err(|error| error.to_string())?;
+                self.replay = Some(
+                    text.parse()
+                        .map_err(|error| format!("{}: {}", value, error))?,
+                );
+            }
+            "variants" => self.variants = parse(name, value)?,
+            "output" => self.output = PathBuf::from(value),
+            "log" => self.log = Some(PathBuf::from(value)),
+            "frame-every" => self.frame_every = Some(u64::max(1, parse(name, value)?)),
+            "fps" => self.frames_per_second = parse(name, value)?,
+            "sweep" if self.sweeps.len() == 2 => {
+                return Err(String::from(
+                    "a contact sheet sweeps at most two parameters",
+                ))
+            }
+            "sweep" => self.sweeps.push(value.parse()?),
+            _ => return Err(format!("unknown option --{}", name)),
+        }
+        Ok(())
+    }
+
+    // a color after --background transparent makes it opaque again
+    fn background(&mut self) -> &mut Background {
+        self.background.get_or_insert_with(Background::white)
+    }
+
+    fn read_config(&mut self, path: &Path) -> Result<(), String> {
+        let text =
+            fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, value) = line.split_once('=').unwrap_or((line, ""));
+            self.set(name.trim(), value.trim())?;
+        }
+        Ok(())
+    }
+
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut settings = Settings::new();
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("expected an option, got {:?}", arg))?;
+            let value = if FLAGS.contains(&name) {
+                String::new()
+            } else {
+                args.next()
+                    .ok_or_else(|| format!("--{} needs a value", name))?
+            };
+            settings.set(name, &value)?;
+        }
+        // other options win over --look, whichever comes first
+        for (name, value) in std::mem::take(&mut settings.look_options) {
+            if !settings.given.contains(&name) {
+                settings.set(&name, &value)?;
+            }
+        }
+        if settings.replay.is_some() {
+            if let Some(name) = settings
+                .given
+                .iter()
+                .find(|name| NOT_WITH_REPLAY.contains(&name.as_str()))
+            {
+                return Err(format!(
+                    "--{} would be ignored, the replayed log decides how it starts",
+                    name
+                ));
+            }
+        }
+        Ok(settings)
+    }
+
+    // overrides are set after the parameters from the options, before anything is added
+    fn universe(&self, seed: u64, overrides: &[(Parameter, f64)]) -> Universe {
+        if let Some(log) = &self.replay {
+            return Universe::replay(log);
+        }
+        let mut universe = Universe::new(self.width, self.height, seed);
+        if let Some(look) = &self.look {
+            look.apply(&mut universe).unwrap();
+        }
+        for (parameter, value) in self.parameters.iter().chain(overrides.iter()) {
+            universe.perform(Action::SET(*parameter, *value));
+        }
+        // COLORMODE switches between the two, so it is only sent when the mode is the other one
+        if let Some(mode) = self.color_mode {
+            if universe.config.color_mode != mode {
+                universe.perform(Action::COLORMODE);
+            }
+        }
+        if let Some(blend) = self.blend {
+            if universe.config.blend != blend {
+                universe.perform(Action::BLEND(blend));
+            }
+        }
+        let stroke = self.stroke.on(universe.config.stroke);
+        if stroke != universe.config.stroke {
+            universe.perform(Action::STROKE(stroke));
+        }
+        for _ in 0..self.circles {
+            universe.perform(Action::CIRCLE(None));
+        }
+        for _ in 0..self.apples {
+            universe.perform(Action::APPLE(None));
+        }
+        universe
+    }
+
+    fn steps(&self) -> u64 {
+        match (self.steps, &self.replay) {
+            (Some(steps), _) => steps,
+            (None, Some(log)) => log.ticks,
+            (None, None) => 1000,
+        }
+    }
+
+    // with more than one variant, FILE.png becomes FILE-0000.png, FILE-0001.png, ...
+    fn numbered(&self, path: &Path, variant: u32, extension: &str) -> PathBuf {
+        if self.variants <= 1 {
+            return path.to_path_buf();
+        }
+        let stem = path.file_stem().map_or(String::from("art"), |stem| {
+            stem.to_string_lossy().into_owned()
+        });
+        let extension = path
+            .extension()
+            .map_or(String::from(extension), |extension| {
+                extension.to_string_lossy().into_owned()
+            });
+        path.with_file_name(format!("{}-{:04}.{}", stem, variant, extension))
+    }
+
+    fn output(&self, variant: u32) -> PathBuf {
+        self.numbered(&self.output, variant, "png")
+    }
+
+    fn log(&self, variant: u32) -> Option<PathBuf> {
+        self.log
+            .as_ref()
+            .map(|log| self.numbered(log, variant, "txt"))
+    }
+}
+
+fn draw(universe: &mut Universe, steps: u64, surface: &mut impl Surface) {
+    for _ in 0..steps {
+        universe.tick();
+        if universe.take_clear_request() {
+            surface.clear();
+        }
+        if universe.take_resize_request() {
+            surface.resize(universe.circle_config.width, universe.circle_config.height);
+        }
+        raster::render(universe, surface);
+    }
+}
+
+// like draw, keeping every `every`th tick as a frame
+fn record(universe: &mut Universe, steps: u64, every: u64, raster: &mut Raster) -> Animation {
+    let mut animation = Animation::new(raster.width, raster.height);
+    for _ in 0..steps {
+        universe.tick();
+        if universe.take_clear_request() {
+            raster.clear();
+        }
+        // frames have to be the same size, so the animation starts over at the new one
+        if universe.take_resize_request() {
+            raster.resize(universe.circle_config.width, universe.circle_config.height);
+            animation = Animation::new(raster.width, raster.height);
+        }
+        raster::render(universe, raster);
+        if universe.ticks % every == 0 {
+            animation.push(raster.flattened().pixels()).unwrap();
+        }
+    }
+    animation
+}
+
+// a gif, or numbered pngs next to the output
+fn write_animation(animation: &Animation, frames_per_second: f64, output: &Path) -> io::Result<()> {
+    let to_io_error = |error| io::Error::new(io::ErrorKind::Other, error);
+    if output
+        .extension()
+        .map_or(false, |extension| extension == "gif")
+    {
+        let gif = animation.to_gif(frames_per_second).map_err(to_io_error)?;
+        return fs::write(output, gif);
+    }
+    let stem = output.file_stem().map_or(String::from("art"), |stem| {
+        stem.to_string_lossy().into_owned()
+    });
+    for (index, frame) in animation.frames().iter().enumerate() {
+        fs::write(
+            output.with_file_name(format!("{}-{:04}.png", stem, index + 1)),
+            frame,
+        )?;
+    }
+    Ok(())
+}
+
+// every combination of the swept values, drawn from the same seed
+fn contact_sheet(settings: &Settings, seed: u64, output: &Path) -> Result<(), String> {
+    if output
+        .extension()
+        .map_or(false, |extension| extension == "svg")
+    {
+        return Err(String::from("contact sheets are only drawn as png"));
+    }
+    let columns = &settings.sweeps[0];
+    let rows = settings.sweeps.get(1);
+    let row_values = rows.map_or(vec![None], |rows| {
+        rows.values.iter().copied().map(Some).collect()
+    });
+
+    let mut cells = vec![];
+    for row_value in row_values.iter() {
+        for column_value in columns.values.iter() {
+            let mut overrides = vec![(columns.parameter, *column_value)];
+            let mut caption = vec![format!("{}={}", columns.name, label(*column_value))];
+            if let (Some(rows), Some(row_value)) = (rows, row_value) {
+                overrides.push((rows.parameter, *row_value));
+                caption.push(format!("{}={}", rows.name, label(*row_value)));
+            }
+
+            let mut universe = settings.universe(seed, &overrides);
+            let mut raster = Raster::new(
+                universe.config.initial_width,
+                universe.config.initial_height,
+                settings.scale,
+            );
+            raster.background = settings.background;
+            draw(&mut universe, settings.steps(), &mut raster);
+            cells.push(Cell {
+                raster: raster.flattened(),
+                caption,
+            });
+        }
+    }
+
+    sheet::contact_sheet(&cells, columns.values.len())
+        .write_png(output)
+        .map_err(|error| format!("{}: {}", output.display(), error))?;
+    println!("{} (seed {})", output.display(), seed);
+    Ok(())
+}
+
+fn run() -> Result<(), String> {
+    let settings = Settings::from_args(env::args().skip(1))?;
+
+    if let Some(path) = &settings.save_look {
+        let name = path.file_stem().map_or(String::from("art"), |stem| {
+            stem.to_string_lossy().into_owned()
+        });
+        let mut look = preset::Preset::capture(&name, &settings.universe(settings.seed, &[]));
+        // the page keeps the background for itself, but a look from here can carry it
+        if let Some(background) = settings.background {
+            look.values.extend(
+                [
+                    ("gradient", String::from(background.fill.name())),
+                    ("background", String::from(&to_hex(background.color)[1..])),
+                    (
+                        "background-to",
+                        String::from(&to_hex(background.to_color)[1..]),
+                    ),
+                    ("grain", background.grain.to_string()),
+                ]
+                .iter()
+                .map(|(name, value)| (String::from(*name), value.clone())),
+            );
+        }
+        fs::write(path, format!("{}\n", look.to_json()))
+            .map_err(|error| format!("{}: {}", path.display(), error))?;
+    }
+
+    for variant in 0..settings.variants {
+        let seed = settings.seed + variant as u64;
+        if !settings.sweeps.is_empty() {
+            contact_sheet(&settings, seed, &settings.output(variant))?;
+            continue;
+        }
+
+        let mut universe = settings.universe(seed, &[]);
+        let (width, height) = (
+            universe.config.initial_width,
+            universe.config.initial_height,
+        );
+        let output = settings.output(variant);
+        let extension = output.extension().map_or(String::new(), |extension| {
+            extension.to_string_lossy().into_owned()
+        });
+        let frame_every = match extension.as_str() {
+            "gif" => Some(settings.frame_every.unwrap_or(10)),
+            _ => settings.frame_every,
+        };
+
+        let written = if let Some(every) = frame_every {
+            if extension == "svg" {
+                return Err(String::from("frames are only drawn as png or gif"));
+            }
+            let mut raster = Raster::new(width, height, settings.scale);
+            raster.background = settings.background;
+            let animation = record(&mut universe, settings.steps(), every, &mut raster);
+            let written = write_animation(&animation, settings.frames_per_second, &output);
+            match extension.as_str() {
+                "gif" => written,
+                _ => written.and_then(|_| raster.write_png(&output)),
+            }
+        } else if extension == "svg" {
+            let mut svg = Svg::new(width, height, settings.scale);
+            svg.background = settings.background;
+            draw(&mut universe, settings.steps(), &mut svg);
+            svg.write(&output)
+        } else {
+            let mut raster = Raster::new(width, height, settings.scale);
+            raster.background = settings.background;
+            draw(&mut universe, settings.steps(), &mut raster);
+            raster.write_png(&output)
+        };
+        written.map_err(|error| format!("{}: {}", output.display(), error))?;
+        if let Some(log) = &settings.log(variant) {
+            fs::write(log, universe.to_log().to_string())
+                .map_err(|error| format!("{}: {}", log.display(), error))?;
+        }
+        println!("{} (seed {})", output.display(), universe.seed);
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("art: {}", error);
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sweeps_spread_their_values_evenly() {
+        let sweep: Sweep = "radius=10:20:3".parse().unwrap();
+        assert_eq!(sweep.name, "radius");
+        assert_eq!(sweep.parameter, Parameter::RADIUS);
+        assert_eq!(sweep.values, vec![10.0, 15.0, 20.0]);
+        let single: Sweep = "max-color-delta=4:8:1".parse().unwrap();
+        assert_eq!(single.values, vec![4.0]);
+    }
+
+    #[test]
+    fn bad_sweeps_are_refused() {
+        assert!("radius=10:20:0".parse::<Sweep>().is_err());
+        assert!("radius=10:20".parse::<Sweep>().is_err());
+        assert!("radius:10:20:3".parse::<Sweep>().is_err());
+        assert!("radius=ten:20:3".parse::<Sweep>().is_err());
+        assert!("circles=1:2:2".parse::<Sweep>().is_err());
+        assert!("apple-steps=0:10:3".parse::<Sweep>().is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::f64;
+use wasm_bindgen::prelude::*;
+
+use crate::circle::{Position, Status, Universe};
+use crate::stroke::{Stroke, StrokeColor};
+use crate::{context, highlight, selection};
+
+// velocity vectors are drawn this many times longer than one tick's move
+const VELOCITY_SCALE: f64 = 5.0;
+
+// what is drawn over the live circles, none of it ends up in the picture
+struct Overlay {
+    outline: bool,
+    crosshair: bool,
+    velocity: bool,
+    labels: bool,
+    trails: bool,
+    countdown: bool,
+    trail_length: usize,
+    // the last positions of each circle, oldest first, one per frame
+    trail: Vec<VecDeque<Position>>,
+    last_tick: u64,
+}
+
+thread_local! {
+    static OVERLAY: RefCell<Overlay> = RefCell::new(Overlay {
+        outline: true,
+        crosshair: false,
+        velocity: false,
+        labels: false,
+        trails: false,
+        countdown: false,
+        trail_length: 10,
+        trail: vec![],
+        last_tick: 0,
+    });
+}
+
+impl Overlay {
+    fn remember(&mut self, universe: &Universe) {
+        if !self.trails || self.trail.len() != universe.circles.len() {
+            self.trail = vec![VecDeque::new(); universe.circles.len()];
+        }
+        if !self.trails || universe.ticks == self.last_tick {
+            return;
+        }
+        self.last_tick = universe.ticks;
+        for (trail, circle) in self.trail.iter_mut().zip(universe.circles.iter()) {
+            trail.push_back(circle.position.clone());
+            while trail.len() > self.trail_length {
+                trail.pop_front();
+            }
+        }
+    }
+}
+
+fn pen(context: &web_sys::CanvasRenderingContext2d, style: &str, width: f64) {
+    context.set_stroke_style(&JsValue::from_str(style));
+    context.set_line_width(width);
+    context.set_line_dash(&js_sys::Array::new()).unwrap();
+}
+
+fn line(context: &web_sys::CanvasRenderingContext2d, from: (f64, f64), to: (f64, f64)) {
+    context.begin_path();
+    context.move_to(from.0, from.1);
+    context.line_to(to.0, to.1);
+    context.stroke();
+}
+
+// call once a frame, after clearing the overlay canvas
+pub fn draw(universe: &Universe, canvas: &web_sys::HtmlCanvasElement) {
+    OVERLAY.with(|overlay| {
+        let mut overlay = overlay.borrow_mut();
+        overlay.remember(universe);
+        let context = context(canvas);
+
+        selection::refresh(universe);
+        if let Some(circle) = selection::selected_circle(universe) {
+            pen(&context, "rgba(0, 0, 0, 0.8)", 1.5);
+            context
+                .set_line_dash(&js_sys::Array::of2(
+                    &JsValue::from_f64(4.0),
+                    &JsValue::from_f64(3.0),
+                ))
+                .unwrap();
+            context.begin_path();
+            context
+                .arc(
+                    circle.position.x,
+                    circle.position.y,
+                    circle.radius + 5.0,
+                    0.0,
+                    f64::consts::PI * 2.0,
+                )
+                .unwrap();
+            context.stroke();
+        }
+
+        if overlay.trails {
+            for (trail, circle) in overlay.trail.iter().zip(universe.circles.iter()) {
+                pen(&context, &StrokeColor::DARKER.css(circle), 1.0);
+                for (age, position) in trail.iter().enumerate() {
+                    context.set_global_alpha(0.5 * (age + 1) as f64 / (trail.len() + 1) as f64);
+                    context.begin_path();
+                    context
+                        .arc(
+                            position.x,
+                            position.y,
+                            circle.radius,
+                            0.0,
+                            f64::consts::PI * 2.0,
+                        )
+                        .unwrap();
+                    context.stroke();
+                }
+            }
+            context.set_global_alpha(1.0);
+        }
+
+        if overlay.outline {
+            if let Status::RUNNING = universe.config.status {
+                highlight(universe, canvas, &Stroke::of_color(StrokeColor::DARKER));
+            }
+        }
+
+        if overlay.crosshair {
+            pen(&context, "rgba(0, 0, 0, 0.6)", 1.0);
+            for circle in universe.circles.iter() {
+                let (x, y) = (circle.position.x, circle.position.y);
+                let reach = circle.radius + 6.0;
+                line(&context, (x - reach, y), (x + reach, y));
+                line(&context, (x, y - reach), (x, y + reach));
+            }
+        }
+
+        if overlay.velocity {
+            pen(&context, "rgba(200, 0, 0, 0.8)", 1.5);
+            for circle in universe.circles.iter() {
+                let (x, y) = (circle.position.x, circle.position.y);
+                let dx = (x - circle.previous.x) * VELOCITY_SCALE;
+                let dy = (y - circle.previous.y) * VELOCITY_SCALE;
+                if dx == 0.0 && dy == 0.0 {
+                    continue;
+                }
+                let tip = (x + dx, y + dy);
+                line(&context, (x, y), tip);
+                let angle = dy.atan2(dx);
+                for side in [-0.5, 0.5] {
+                    let back = (
+                        tip.0 - 5.0 * (angle + side).cos(),
+                        tip.1 - 5.0 * (angle + side).sin(),
+                    );
+                    line(&context, tip, back);
+                }
+            }
+        }
+
+        if overlay.labels {
+            context.set_font("12px sans-serif");
+            context.set_fill_style(&JsValue::from_str("black"));
+            for (index, circle) in universe.circles.iter().enumerate() {
+                context
+                    .fill_text(
+                        &index.to_string(),
+                        circle.position.x + circle.radius + 2.0,
+                        circle.position.y - circle.radius - 2.0,
+                    )
+                    .unwrap();
+            }
+        }
+
+        // the part of the ring left is the part of its steps the apple has left
+        if overlay.countdown {
+            pen(&context, "rgba(200, 30, 30, 0.8)", 2.0);
+            for apple in universe.apples.iter() {
+                let fraction = apple.steps as f64
+                    / u32::max(1, u32::max(universe.config.apple_steps, apple.steps)) as f64;
+                let start = -f64::consts::FRAC_PI_2;
+                context.begin_path();
+                context
+                    .arc(
+                        apple.circle.position.x,
+                        apple.circle.position.y,
+                        apple.circle.radius + 3.0,
+                        start,
+                        start + fraction * f64::consts::PI * 2.0,
+                    )
+                    .unwrap();
+                context.stroke();
+            }
+        }
+    });
+}
+
+fn option(f: impl FnOnce(&Overlay) -> bool) -> bool {
+    OVERLAY.with(|overlay| f(&overlay.borrow()))
+}
+
+fn set_option(f: impl FnOnce(&mut Overlay)) {
+    OVERLAY.with(|overlay| f(&mut overlay.borrow_mut()));
+}
+
+pub fn outline(_universe: &Universe) -> bool {
+    option(|overlay| overlay.outline)
+}
+
+pub fn set_outline(_universe: &mut Universe, value: bool) {
+    set_option(|overlay| overlay.outline = value);
+}
+
+pub fn crosshair(_universe: &Universe) -> bool {
+    option(|overlay| overlay.crosshair)
+}
+
+pub fn set_crosshair(_universe: &mut Universe, value: bool) {
+    set_option(|overlay| overlay.crosshair = value);
+}
+
+pub fn velocity(_universe: &Universe) -> bool {
+    option(|overlay| overlay.velocity)
+}
+
+pub fn set_velocity(_universe: &mut Universe, value: bool) {
+    set_option(|overlay| overlay.velocity = value);
+}
+
+pub fn labels(_universe: &Universe) -> bool {
+    option(|overlay| overlay.labels)
+}
+
+pub fn set_labels(_universe: &mut Universe, value: bool) {
+    set_option(|overlay| overlay.labels = value);
+}
+
+pub fn trails(_universe: &Universe) -> bool {
+    option(|overlay| overlay.trails)
+}
+
+pub fn set_trails(_universe: &mut Universe, value: bool) {
+    set_option(|overlay| overlay.trails = value);
+}
+
+pub fn countdown(_universe: &Universe) -> bool {
+    option(|overlay| overlay.countdown)
+}
+
+pub fn set_countdown(_universe: &mut Universe, value: bool) {
+    set_option(|overlay| overlay.countdown = value);
+}
+
+pub fn trail_length(_universe: &Universe) -> f64 {
+    OVERLAY.with(|overlay| overlay.borrow().trail_length as f64)
+}
+
+pub fn set_trail_length(_universe: &mut Universe, value: f64) {
+    set_option(|overlay| overlay.trail_length = value as usize);
+}
@@ -0,0 +1,150 @@
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::background::to_hex;
+use crate::circle::Universe;
+use crate::registry::{self, Group, Kind, Tunable};
+use crate::{
+    document, new_control_div, refresh_choices, window, CheckboxConfig, ColorConfig, RadioConfig,
+    SelectConfig, SliderConfig,
+};
+
+const MAX_RADIOS: usize = 3;
+
+// the widget for one tunable, in a control div
+pub fn control(tunable: &Tunable, universe: &Arc<Mutex<Universe>>) -> web_sys::HtmlDivElement {
+    match tunable.kind {
+        Kind::RANGE {
+            min,
+            max,
+            step,
+            get,
+            set,
+        } => SliderConfig::create_slider(
+            &SliderConfig {
+                id: tunable.id(),
+                title: String::from(tunable.title),
+                left_label: tunable.label.map(String::from),
+                min,
+                max,
+                step,
+                of_universe: get,
+                on_update: set,
+                undoable: tunable.undoable,
+            },
+            universe,
+        ),
+        Kind::TOGGLE { get, set } => CheckboxConfig {
+            id: tunable.id(),
+            text: String::from(tunable.label.unwrap_or(tunable.title)),
+            of_universe: get,
+            on_click: set,
+            undoable: tunable.undoable,
+        }
+        .new_checkbox(universe),
+        // a few choices fit side by side, more go in a dropdown
+        Kind::CHOICE { names, get, set } if names().len() <= MAX_RADIOS => RadioConfig {
+            id: tunable.id(),
+            title: String::from(tunable.title),
+            options: names(),
+            of_universe: get,
+            on_update: set,
+            undoable: tunable.undoable,
+        }
+        .new_radio_group(universe),
+        Kind::CHOICE { names, get, set } => SelectConfig {
+            id: tunable.id(),
+            title: String::from(tunable.title),
+            left_label: tunable.label.map(String::from),
+            options: names(),
+            of_universe: get,
+            on_update: set,
+            undoable: tunable.undoable,
+        }
+        .new_select(universe),
+        Kind::COLOR { get, set } => ColorConfig {
+            id: tunable.id(),
+            text: String::from(tunable.label.unwrap_or(tunable.title)),
+            of_universe: get,
+            on_update: set,
+            undoable: tunable.undoable,
+        }
+        .new_color_picker(universe),
+    }
+}
+
+// all the tunables of a group together in one control div
+pub fn group_div(group: Group, universe: &Arc<Mutex<Universe>>) -> web_sys::HtmlDivElement {
+    let div = new_control_div();
+    div.set_id(&format!("{}-group", group.name()));
+    for tunable in registry::tunables()
+        .iter()
+        .filter(|tunable| tunable.group == group)
+    {
+        let control = control(tunable, universe);
+        while let Some(child) = control.first_child() {
+            div.append_child(&child).unwrap();
+        }
+    }
+    div
+}
+
+// settings in the page's address
+pub fn load_link(universe: &mut Universe) {
+    let search = window().location().search().unwrap();
+    if let Err(error) = registry::apply_query(universe, &search) {
+        web_sys::console::log(&js_sys::Array::from(&JsValue::from_str(&format!(
+            "Couldn't use the link's settings, {}",
+            error
+        ))));
+    }
+    refresh(universe);
+}
+
+// puts the settings in the address bar, to bookmark or share
+pub fn update_link(universe: &mut Universe) {
+    let url = format!("?{}", registry::to_query(universe));
+    window()
+        .history()
+        .unwrap()
+        .replace_state_with_url(&JsValue::NULL, "", Some(&url))
+        .unwrap();
+}
+
+// puts every control back in step with the universe, after something other than the control changed it
+pub fn refresh(universe: &Universe) {
+    for tunable in registry::tunables().iter() {
+        let id = tunable.id();
+        let element = match document().get_element_by_id(&id) {
+            Some(element) => element,
+            None => continue,
+        };
+        match tunable.kind {
+            Kind::RANGE { get, .. } => {
+                let value = get(universe).to_string();
+                element
+                    .dyn_into::<web_sys::HtmlInputElement>()
+                    .unwrap()
+                    .set_value(&value);
+                if let Some(display) = document().get_element_by_id(&format!("{}-input", id)) {
+                    display
+                        .dyn_into::<web_sys::HtmlInputElement>()
+                        .unwrap()
+                        .set_value(&value);
+                }
+            }
+            Kind::TOGGLE { get, .. } => element
+                .dyn_into::<web_sys::HtmlInputElement>()
+                .unwrap()
+                .set_checked(get(universe)),
+            Kind::COLOR { get, .. } => element
+                .dyn_into::<web_sys::HtmlInputElement>()
+                .unwrap()
+                .set_value(&to_hex(get(universe))),
+            // every dropdown and radio group is done below, not just the tunables
+            Kind::CHOICE { .. } => {}
+        }
+    }
+    refresh_choices(universe);
+}
@@ -0,0 +1,331 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::circle::Universe;
+use crate::registry;
+
+// a named set of tunable values, kept as json in the browser's storage or in files
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preset {
+    pub name: String,
+    // registry names and values, like the pairs of a link
+    pub values: Vec<(String, String)>,
+}
+
+impl Preset {
+    // only what the universe keeps, the size and the page's own settings stay where they are
+    pub fn capture(name: &str, universe: &Universe) -> Self {
+        Preset {
+            name: String::from(name),
+            values: registry::tunables()
+                .iter()
+                .filter(|tunable| !tunable.page)
+                .map(|tunable| (String::from(tunable.name), tunable.value(universe)))
+                .collect(),
+        }
+    }
+
+    // names it doesn't know are skipped, like in links
+    pub fn apply(&self, universe: &mut Universe) -> Result<(), String> {
+        for (name, value) in self.values.iter() {
+            if let Some(tunable) = registry::tunable(name) {
+                tunable.set_value(universe, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    // one flat object, the name first
+    pub fn to_json(&self) -> String {
+        let mut fields = vec![format!("  {}: {}", quote("name"), quote(&self.name))];
+        for (name, value) in self.values.iter() {
+            fields.push(format!("  {}: {}", quote(name), quote(value)));
+        }
+        format!("{{\n{}\n}}", fields.join(",\n"))
+    }
+
+    pub fn of_json(text: &str) -> Result<Self, String> {
+        let mut parser = Parser::new(text);
+        let preset = parser.preset()?;
+        parser.end()?;
+        Ok(preset)
+    }
+}
+
+pub fn to_json_list(presets: &[Preset]) -> String {
+    let presets: Vec<String> = presets.iter().map(|preset| preset.to_json()).collect();
+    format!("[{}]", presets.join(",\n"))
+}
+
+pub fn of_json_list(text: &str) -> Result<Vec<Preset>, String> {
+    let mut parser = Parser::new(text);
+    let mut presets = vec![];
+    parser.expect('[')?;
+    if !parser.next_is(']') {
+        loop {
+            presets.push(parser.preset()?);
+            if !parser.next_is(',') {
+                break;
+            }
+            parser.expect(',')?;
+        }
+    }
+    parser.expect(']')?;
+    parser.end()?;
+    Ok(presets)
+}
+
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// just enough json for presets, objects of strings and lists of them
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Parser {
+            chars: text.chars().peekable(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().map_or(false, |c| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn next_is(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        self.chars.peek() == Some(&c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("expected {:?}, found {:?}", expected, c)),
+            None => Err(format!("expected {:?}, found the end", expected)),
+        }
+    }
+
+    fn end(&mut self) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            None => Ok(()),
+            Some(c) => Err(format!("expected the end, found {:?}", c)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut text = String::new();
+        loop {
+            match self.chars.next() {
+                None => return Err(String::from("a string doesn't end")),
+                Some('"') => return Ok(text),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some('r') => text.push('\r'),
+                    Some('b') => text.push('\u{8}'),
+                    Some('f') => text.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.chars.next()).collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| format!("bad escape \\u{}", hex))?;
+                        text.push(c);
+                    }
+                    Some(c) => text.push(c),
+                    None => return Err(String::from("a string doesn't end")),
+                },
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    // a number or true or false, kept as the text it was written as
+    fn bare(&mut self) -> Result<String, String> {
+        self.skip_whitespace();
+        let mut text = String::new();
+        while let Some(c) = self
+            .chars
+            .peek()
+            .copied()
+            .filter(|c| c.is_alphanumeric() || "+-.".contains(*c))
+        {
+            text.push(c);
+            self.chars.next();
+        }
+        match text.as_str() {
+            "" => Err(String::from("expected a value")),
+            _ => Ok(text),
+        }
+    }
+
+    fn preset(&mut self) -> Result<Preset, String> {
+        let mut name = None;
+        let mut values = vec![];
+        self.expect('{')?;
+        if !self.next_is('}') {
+            loop {
+                let key = self.string()?;
+                self.expect(':')?;
+                let value = if self.next_is('"') {
+                    self.string()?
+                } else {
+                    self.bare()?
+                };
+                match key.as_str() {
+                    "name" => name = Some(value),
+                    _ => values.push((key, value)),
+                }
+                if !self.next_is(',') {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+        self.expect('}')?;
+        Ok(Preset {
+            name: name.ok_or_else(|| String::from("a preset has no name"))?,
+            values,
+        })
+    }
+}
+
+fn built_in(name: &str, values: &[(&str, &str)]) -> Preset {
+    Preset {
+        name: String::from(name),
+        values: values
+            .iter()
+            .map(|(name, value)| (String::from(*name), String::from(*value)))
+            .collect(),
+    }
+}
+
+// starting points, they only set how the circles look and move
+pub fn built_ins() -> Vec<Preset> {
+    vec![
+        built_in(
+            "watercolor",
+            &[
+                ("radius", "40"),
+                ("distance", "3"),
+                ("color", "2"),
+                ("color-mode", "hsl"),
+                ("blend", "multiply"),
+                ("stroke-color", "fill"),
+                ("stroke-width", "0"),
+                ("dash", "solid"),
+                ("hollow", "false"),
+            ],
+        ),
+        built_in(
+            "confetti",
+            &[
+                ("radius", "4"),
+                ("distance", "40"),
+                ("color", "50"),
+                ("color-mode", "rgb"),
+                ("blend", "normal"),
+                ("stroke-color", "fill"),
+                ("stroke-width", "0"),
+                ("dash", "solid"),
+                ("hollow", "false"),
+            ],
+        ),
+        built_in(
+            "ink",
+            &[
+                ("radius", "2"),
+                ("distance", "4"),
+                ("color", "1"),
+                ("color-mode", "rgb"),
+                ("blend", "multiply"),
+                ("stroke-color", "black"),
+                ("stroke-width", "1"),
+                ("dash", "solid"),
+                ("hollow", "true"),
+            ],
+        ),
+    ]
+}
+
+pub fn built_in_named(name: &str) -> Option<Preset> {
+    built_ins().into_iter().find(|preset| preset.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_leave_the_size_and_page_out() {
+        let preset = Preset::capture("look", &Universe::new(300.0, 200.0, 1));
+        let has = |name: &str| preset.values.iter().any(|(key, _)| key == name);
+        assert!(has("radius"));
+        assert!(has("blend"));
+        assert!(!has("width"));
+        assert!(!has("height"));
+        assert!(!has("trails"));
+        assert!(!has("background"));
+    }
+
+    #[test]
+    fn lists_read_back_what_they_write() {
+        let presets = vec![
+            built_in("plain", &[("radius", "4"), ("blend", "screen")]),
+            built_in(
+                "odd \"name\"\\ with\ttabs\nand lines",
+                &[("hollow", "true")],
+            ),
+            built_in("empty", &[]),
+        ];
+        assert_eq!(of_json_list(&to_json_list(&presets)), Ok(presets));
+        assert_eq!(of_json_list(&to_json_list(&[])), Ok(vec![]));
+    }
+
+    #[test]
+    fn escapes_decode_to_what_they_stand_for() {
+        let text = r#"[{"name": "a\"b\\c\/d\be\ff\ng\rh\tié"}]"#;
+        let presets = of_json_list(text).unwrap();
+        assert_eq!(presets[0].name, "a\"b\\c/d\u{8}e\u{c}f\ng\rh\ti\u{e9}");
+    }
+
+    #[test]
+    fn numbers_and_booleans_are_kept_as_written() {
+        let presets = of_json_list(r#" [ { "name" : "n", "radius": 4.5, "hollow": true } ] "#);
+        assert_eq!(
+            presets,
+            Ok(vec![built_in(
+                "n",
+                &[("radius", "4.5"), ("hollow", "true")]
+            )])
+        );
+    }
+
+    #[test]
+    fn broken_lists_are_refused() {
+        assert!(of_json_list(r#"[{"radius": "4"}]"#).is_err());
+        assert!(of_json_list(r#"[{"name": "a"}"#).is_err());
+        assert!(of_json_list(r#"[{"name": "a}]"#).is_err());
+        assert!(of_json_list(r#"[{"name": "a"}] and more"#).is_err());
+        assert!(of_json_list(r#"[{"name": "\uzzzz"}]"#).is_err());
+    }
+}
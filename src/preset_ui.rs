@@ -0,0 +1,215 @@
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::circle::Universe;
+use crate::preset::{self, Preset};
+use crate::{
+    document, download, history, new_control_div, panel, storage, ButtonConfig, ButtonText,
+    TextConfig,
+};
+
+const STORAGE_KEY: &str = "presets";
+const PRESET_SELECT_ID: &str = "preset-select";
+const PRESET_NAME_ID: &str = "preset-name-input";
+
+thread_local! {
+    // what the next save is called, and what delete and download act on
+    static NAME: RefCell<String> = RefCell::new(String::new());
+}
+
+fn log(text: &str) {
+    web_sys::console::log(&js_sys::Array::from(&JsValue::from_str(text)));
+}
+
+fn saved() -> Vec<Preset> {
+    let text = match storage().and_then(|storage| storage.get_item(STORAGE_KEY).unwrap()) {
+        Some(text) => text,
+        None => return vec![],
+    };
+    preset::of_json_list(&text).unwrap_or_else(|error| {
+        log(&format!("Couldn't read the saved presets, {}", error));
+        vec![]
+    })
+}
+
+fn store(presets: &[Preset]) {
+    if let Some(storage) = storage() {
+        storage
+            .set_item(STORAGE_KEY, &preset::to_json_list(presets))
+            .unwrap();
+    }
+}
+
+// the built in ones first, a saved one with the same name takes its place
+fn all() -> Vec<Preset> {
+    let saved = saved();
+    let mut presets: Vec<Preset> = preset::built_ins()
+        .into_iter()
+        .filter(|built_in| !saved.iter().any(|preset| preset.name == built_in.name))
+        .collect();
+    presets.extend(saved);
+    presets
+}
+
+fn select() -> web_sys::HtmlSelectElement {
+    document()
+        .get_element_by_id(PRESET_SELECT_ID)
+        .unwrap()
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap()
+}
+
+fn fill_select(select: &web_sys::HtmlSelectElement) {
+    select.set_inner_html("");
+    let option = web_sys::HtmlOptionElement::new_with_text_and_value("Presets", "").unwrap();
+    option.set_disabled(true);
+    select.append_child(&option).unwrap();
+    for preset in all().iter() {
+        let option =
+            web_sys::HtmlOptionElement::new_with_text_and_value(&preset.name, &preset.name)
+                .unwrap();
+        select.append_child(&option).unwrap();
+    }
+    select.set_value(&NAME.with(|name| name.borrow().clone()));
+    if select.selected_index() < 0 {
+        select.set_value("");
+    }
+}
+
+fn name(_universe: &Universe) -> String {
+    NAME.with(|name| name.borrow().clone())
+}
+
+fn set_name(_universe: &mut Universe, value: &str) {
+    NAME.with(|name| *name.borrow_mut() = String::from(value.trim()));
+}
+
+fn show_name() {
+    if let Some(input) = document().get_element_by_id(PRESET_NAME_ID) {
+        input
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .set_value(&NAME.with(|name| name.borrow().clone()));
+    }
+}
+
+// logged like any other change, so it can be undone and replayed
+fn apply(universe: &mut Universe, name: &str) {
+    let preset = match all().into_iter().find(|preset| preset.name == name) {
+        Some(preset) => preset,
+        None => return,
+    };
+    history::checkpoint(universe);
+    if let Err(error) = preset.apply(universe) {
+        log(&format!("Couldn't use the preset {}, {}", name, error));
+    }
+    panel::refresh(universe);
+    set_name(universe, name);
+    show_name();
+}
+
+fn save(universe: &mut Universe) {
+    let name = name(universe);
+    if name.is_empty() {
+        log("A preset needs a name");
+        return;
+    }
+    let mut presets = saved();
+    presets.retain(|preset| preset.name != name);
+    presets.push(Preset::capture(&name, universe));
+    store(&presets);
+    fill_select(&select());
+}
+
+// only saved presets go, a built in one with the same name comes back
+fn delete(universe: &mut Universe) {
+    let name = name(universe);
+    let mut presets = saved();
+    presets.retain(|preset| preset.name != name);
+    store(&presets);
+    fill_select(&select());
+}
+
+// for --look on the command line
+fn download_json(universe: &mut Universe) {
+    let name = match name(universe).as_str() {
+        "" => String::from("preset"),
+        name => String::from(name),
+    };
+    let json = Preset::capture(&name, universe).to_json();
+    let href = format!(
+        "data:application/json;charset=utf-8,{}",
+        js_sys::encode_uri_component(&json)
+    );
+    download(&href, &format!("{}.json", name));
+}
+
+pub fn presets_div(universe: &Arc<Mutex<Universe>>) -> web_sys::HtmlDivElement {
+    let div = new_control_div();
+
+    let select = document()
+        .create_element("select")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap();
+    select.set_id(PRESET_SELECT_ID);
+    select.set_title("Presets");
+    fill_select(&select);
+
+    let universe_clone = Arc::clone(universe);
+    let select_clone = select.clone();
+    let on_change_handler = Closure::wrap(Box::new(move || {
+        apply(&mut universe_clone.lock().unwrap(), &select_clone.value());
+    }) as Box<dyn FnMut()>);
+    select.set_onchange(Some(on_change_handler.as_ref().unchecked_ref()));
+    on_change_handler.forget();
+    div.append_child(&select).unwrap();
+
+    let name_config = TextConfig {
+        id: String::from(PRESET_NAME_ID),
+        text: String::from("📛"),
+        placeholder: String::from("name"),
+        of_universe: name,
+        on_update: set_name,
+        undoable: false,
+    };
+    let name_div = name_config.new_text_input(universe);
+    while let Some(child) = name_div.first_child() {
+        div.append_child(&child).unwrap();
+    }
+
+    let buttons = [
+        (
+            "preset-save-button",
+            "📌",
+            "Save the settings as a preset",
+            save as fn(&mut Universe),
+        ),
+        (
+            "preset-delete-button",
+            "❌",
+            "Delete the saved preset",
+            delete,
+        ),
+        (
+            "preset-download-button",
+            "⬇️",
+            "Download the settings as a preset file",
+            download_json,
+        ),
+    ];
+    for (id, text, title, on_click) in buttons.iter() {
+        let button = ButtonConfig {
+            id: String::from(*id),
+            text: ButtonText::STATIC(String::from(*text)),
+            on_click: *on_click,
+            undoable: false,
+        }
+        .new_button(universe);
+        button.set_title(title);
+        div.append_child(&button).unwrap();
+    }
+    div
+}
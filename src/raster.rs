@@ -0,0 +1,366 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::background::{to_hex, Background, Fill};
+use crate::blend::Blend;
+use crate::circle::{Circle, Universe};
+use crate::stroke::Stroke;
+
+// somewhere to draw circles that isn't a browser canvas
+pub trait Surface {
+    fn draw_circle(&mut self, circle: &Circle, stroke: &Stroke);
+    fn clear(&mut self);
+    // keeps what is drawn where it is, cut off or with blank space added on the right and bottom
+    fn resize(&mut self, width: f64, height: f64);
+    // how the circles drawn from now on mix with what is underneath
+    fn set_blend(&mut self, blend: Blend);
+}
+
+// the same drawing as `render` does on the page
+pub fn render(universe: &Universe, surface: &mut impl Surface) {
+    surface.set_blend(universe.config.blend);
+    for circle in universe
+        .circles
+        .iter()
+        .chain(universe.apples.iter().map(|apple| &apple.circle))
+    {
+        surface.draw_circle(circle, &universe.config.stroke);
+    }
+}
+
+// whether a point this far along a dashed line is on a dash rather than a gap
+fn on_dash(pattern: &[f64], along: f64) -> bool {
+    if pattern.is_empty() {
+        return true;
+    }
+    let mut offset = along % pattern.iter().sum::<f64>();
+    for (index, length) in pattern.iter().enumerate() {
+        if offset < *length {
+            return index % 2 == 0;
+        }
+        offset -= length;
+    }
+    true
+}
+
+fn clamp_unit(value: f64) -> f64 {
+    f64::min(1.0, f64::max(0.0, value))
+}
+
+pub struct Raster {
+    pub width: u32,
+    pub height: u32,
+    scale: f64,
+    blend: Blend,
+    // none for a transparent png
+    pub background: Option<Background>,
+    // rgba, not premultiplied, transparent until something is drawn like a canvas
+    pixels: Vec<u8>,
+}
+
+impl Raster {
+    pub fn new(width: f64, height: f64, scale: f64) -> Self {
+        let width = (width * scale).round() as u32;
+        let height = (height * scale).round() as u32;
+        Raster {
+            width,
+            height,
+            scale,
+            blend: Blend::NORMAL,
+            background: Some(Background::white()),
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    fn composite(&mut self, x: u32, y: u32, rgb: [u8; 3], alpha: f64, blend: Blend) {
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        let pixel = &mut self.pixels[index..index + 4];
+        let below = [
+            pixel[0] as f64 / 255.0,
+            pixel[1] as f64 / 255.0,
+            pixel[2] as f64 / 255.0,
+            pixel[3] as f64 / 255.0,
+        ];
+        let above = [
+            rgb[0] as f64 / 255.0,
+            rgb[1] as f64 / 255.0,
+            rgb[2] as f64 / 255.0,
+            alpha,
+        ];
+        for (value, mixed) in pixel.iter_mut().zip(blend.composite(below, above).iter()) {
+            *value = (clamp_unit(*mixed) * 255.0).round() as u8;
+        }
+    }
+
+    // coverage gets the distance of a pixel's center from the middle of the circle,
+    // and its angle clockwise from the right, the way a canvas arc goes
+    fn paint(
+        &mut self,
+        circle: &Circle,
+        reach: f64,
+        rgb: [u8; 3],
+        opacity: f64,
+        coverage: impl Fn(f64, f64) -> f64,
+    ) {
+        let cx = circle.position.x * self.scale;
+        let cy = circle.position.y * self.scale;
+        let x_min = f64::max(0.0, (cx - reach).floor()) as u32;
+        let y_min = f64::max(0.0, (cy - reach).floor()) as u32;
+        let x_max = f64::min(self.width as f64, (cx + reach).ceil()) as u32;
+        let y_max = f64::min(self.height as f64, (cy + reach).ceil()) as u32;
+        for y in y_min..y_max {
+            for x in x_min..x_max {
+                let (dx, dy) = (x as f64 + 0.5 - cx, y as f64 + 0.5 - cy);
+                let angle = dy.atan2(dx).rem_euclid(2.0 * std::f64::consts::PI);
+                let alpha = opacity * clamp_unit(coverage(f64::hypot(dx, dy), angle));
+                if alpha > 0.0 {
+                    self.composite(x, y, rgb, alpha, self.blend);
+                }
+            }
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, rgb: [u8; 3]) {
+        for y in y..u32::min(y + height, self.height) {
+            for x in x..u32::min(x + width, self.width) {
+                self.composite(x, y, rgb, 1.0, Blend::NORMAL);
+            }
+        }
+    }
+
+    // draws another raster over this one with its top left corner at x, y
+    pub fn paste(&mut self, other: &Raster, x: u32, y: u32) {
+        for row in 0..u32::min(other.height, self.height.saturating_sub(y)) {
+            for column in 0..u32::min(other.width, self.width.saturating_sub(x)) {
+                let index = (row as usize * other.width as usize + column as usize) * 4;
+                let pixel = &other.pixels[index..index + 4];
+                let alpha = pixel[3] as f64 / 255.0;
+                if alpha > 0.0 {
+                    let rgb = [pixel[0], pixel[1], pixel[2]];
+                    self.composite(x + column, y + row, rgb, alpha, Blend::NORMAL);
+                }
+            }
+        }
+    }
+
+    // on the background, the way the page shows it
+    pub fn flattened(&self) -> Raster {
+        let mut flat = Raster::new(self.width as f64, self.height as f64, 1.0);
+        flat.background = None;
+        if let Some(background) = &self.background {
+            flat.pixels = background.pixels(self.width, self.height);
+        }
+        flat.paste(self, 0, 0);
+        flat
+    }
+
+    pub fn write_png(&self, path: &Path) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.flattened().pixels))
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))
+    }
+}
+
+impl Surface for Raster {
+    fn draw_circle(&mut self, circle: &Circle, stroke: &Stroke) {
+        let radius = circle.radius * self.scale;
+
+        if !stroke.hollow {
+            self.paint(
+                circle,
+                radius + 1.0,
+                circle.color.to_rgb(),
+                circle.color.opacity(),
+                |distance, _| radius - distance + 0.5,
+            );
+        }
+
+        if stroke.width <= 0.0 {
+            return;
+        }
+        // canvas strokes are centered on the edge
+        let half_line = 0.5 * stroke.width * self.scale;
+        let pattern: Vec<f64> = stroke
+            .dash
+            .pattern(stroke.width)
+            .iter()
+            .map(|length| length * self.scale)
+            .collect();
+        let (rgb, opacity) = stroke.color.rgba(circle);
+        self.paint(
+            circle,
+            radius + half_line + 1.0,
+            rgb,
+            opacity,
+            |distance, angle| {
+                if on_dash(&pattern, angle * radius) {
+                    half_line - (distance - radius).abs() + 0.5
+                } else {
+                    0.0
+                }
+            },
+        );
+    }
+
+    fn clear(&mut self) {
+        for value in self.pixels.iter_mut() {
+            *value = 0;
+        }
+    }
+
+    fn resize(&mut self, width: f64, height: f64) {
+        let mut resized = Raster::new(width, height, self.scale);
+        let row = u32::min(self.width, resized.width) as usize * 4;
+        for y in 0..u32::min(self.height, resized.height) as usize {
+            let from = y * self.width as usize * 4;
+            let to = y * resized.width as usize * 4;
+            resized.pixels[to..to + row].copy_from_slice(&self.pixels[from..from + row]);
+        }
+        self.width = resized.width;
+        self.height = resized.height;
+        self.pixels = resized.pixels;
+    }
+
+    fn set_blend(&mut self, blend: Blend) {
+        self.blend = blend;
+    }
+}
+
+pub struct Svg {
+    width: f64,
+    height: f64,
+    scale: f64,
+    blend: Blend,
+    pub background: Option<Background>,
+    body: String,
+}
+
+impl Svg {
+    pub fn new(width: f64, height: f64, scale: f64) -> Self {
+        Svg {
+            width,
+            height,
+            scale,
+            blend: Blend::NORMAL,
+            background: Some(Background::white()),
+            body: String::new(),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+            self.width * self.scale,
+            self.height * self.scale,
+            self.width,
+            self.height
+        )?;
+        if let Some(background) = &self.background {
+            write_background(&mut writer, background)?;
+        }
+        writer.write_all(self.body.as_bytes())?;
+        writeln!(writer, "</svg>")
+    }
+}
+
+// gradients as svg gradients, and the paper grain as turbulence
+fn write_background(writer: &mut impl Write, background: &Background) -> io::Result<()> {
+    let stops = format!(
+        r#"<stop offset="0" stop-color="{}"/><stop offset="1" stop-color="{}"/>"#,
+        to_hex(background.color),
+        to_hex(background.to_color)
+    );
+    let fill = match background.fill {
+        Fill::SOLID => to_hex(background.color),
+        Fill::LINEAR => {
+            writeln!(
+                writer,
+                r#"<defs><linearGradient id="background" x1="0" y1="0" x2="0" y2="1">{}</linearGradient></defs>"#,
+                stops
+            )?;
+            String::from("url(#background)")
+        }
+        Fill::RADIAL => {
+            writeln!(
+                writer,
+                r#"<defs><radialGradient id="background" cx="0.5" cy="0.5" r="0.7071">{}</radialGradient></defs>"#,
+                stops
+            )?;
+            String::from("url(#background)")
+        }
+    };
+    writeln!(
+        writer,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        fill
+    )?;
+    if background.grain > 0.0 {
+        writeln!(
+            writer,
+            r#"<filter id="paper"><feTurbulence type="fractalNoise" baseFrequency="0.8" numOctaves="3"/><feColorMatrix type="saturate" values="0"/></filter>"#
+        )?;
+        writeln!(
+            writer,
+            r#"<rect width="100%" height="100%" filter="url(#paper)" opacity="{}" style="mix-blend-mode: multiply"/>"#,
+            background.grain * 0.2
+        )?;
+    }
+    Ok(())
+}
+
+impl Surface for Svg {
+    fn draw_circle(&mut self, circle: &Circle, stroke: &Stroke) {
+        let fill = if stroke.hollow {
+            String::from("none")
+        } else {
+            circle.color()
+        };
+        let mut outline = if stroke.width > 0.0 {
+            format!(
+                " stroke=\"{}\" stroke-width=\"{}\"",
+                stroke.color.css(circle),
+                stroke.width
+            )
+        } else {
+            String::new()
+        };
+        let pattern = stroke.dash.pattern(stroke.width);
+        if stroke.width > 0.0 && !pattern.is_empty() {
+            let lengths: Vec<String> = pattern.iter().map(|length| length.to_string()).collect();
+            outline.push_str(&format!(" stroke-dasharray=\"{}\"", lengths.join(" ")));
+        }
+        let style = match self.blend {
+            Blend::NORMAL => String::new(),
+            blend => format!(" style=\"mix-blend-mode: {}\"", blend.mix_blend_mode()),
+        };
+        self.body.push_str(&format!(
+            "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{}\" fill=\"{}\"{}{}/>\n",
+            circle.position.x, circle.position.y, circle.radius, fill, outline, style
+        ));
+    }
+
+    fn clear(&mut self) {
+        self.body.clear();
+    }
+
+    fn resize(&mut self, width: f64, height: f64) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn set_blend(&mut self, blend: Blend) {
+        self.blend = blend;
+    }
+}
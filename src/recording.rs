@@ -0,0 +1,173 @@
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::animation::Animation;
+use crate::circle::Universe;
+use crate::{context, document, download_bytes, layers, named, RECORD_BUTTON_ID};
+
+struct Recorder {
+    recording: bool,
+    animation: Option<Animation>,
+    // ticks between captured frames
+    every: u64,
+    // a tick to capture went by this animation frame
+    pending: bool,
+    frames_per_second: f64,
+    // seconds of animation, recording stops once there are enough frames
+    duration: f64,
+    // what the record button was last showing
+    shown: String,
+}
+
+impl Recorder {
+    fn max_frames(&self) -> usize {
+        f64::max(1.0, (self.frames_per_second * self.duration).round()) as usize
+    }
+
+    fn capture(&mut self) {
+        let canvas = layers::flatten();
+        let image = context(&canvas)
+            .get_image_data(0.0, 0.0, canvas.width() as f64, canvas.height() as f64)
+            .unwrap();
+        let animation = self
+            .animation
+            .get_or_insert_with(|| Animation::new(image.width(), image.height()));
+        if let Err(error) = animation.push(&image.data()) {
+            log(&format!("Stopped recording, {}", error));
+            self.recording = false;
+            return;
+        }
+        if animation.len() >= self.max_frames() {
+            log("Finished recording");
+            self.recording = false;
+        }
+    }
+}
+
+thread_local! {
+    static RECORDER: RefCell<Recorder> = RefCell::new(Recorder {
+        recording: false,
+        animation: None,
+        every: 10,
+        pending: false,
+        frames_per_second: 20.0,
+        duration: 10.0,
+        shown: String::new(),
+    });
+}
+
+fn log(text: &str) {
+    web_sys::console::log(&js_sys::Array::from(&JsValue::from_str(text)));
+}
+
+// starts a new recording, or stops the current one
+pub fn toggle(_universe: &mut Universe) {
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        recorder.recording = !recorder.recording;
+        recorder.pending = false;
+        if recorder.recording {
+            recorder.animation = None;
+            recorder.capture();
+        }
+    })
+}
+
+pub fn button_display(_universe: &Universe) -> String {
+    RECORDER.with(|recorder| match recorder.borrow().recording {
+        true => String::from("⏹️"),
+        false => String::from("⏺️"),
+    })
+}
+
+// recordings stop themselves, so the record button is brought up to date every frame
+pub fn refresh(universe: &Universe) {
+    let display = button_display(universe);
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        if recorder.shown == display {
+            return;
+        }
+        if let Some(button) = document().get_element_by_id(RECORD_BUTTON_ID) {
+            button
+                .dyn_into::<web_sys::HtmlElement>()
+                .unwrap()
+                .set_inner_text(&display);
+        }
+        recorder.shown = display;
+    });
+}
+
+// call after each tick has been drawn, it only notes that the tick should be captured
+pub fn capture(universe: &Universe) {
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        if recorder.recording && universe.ticks % recorder.every == 0 {
+            recorder.pending = true;
+        }
+    })
+}
+
+// call once a frame after the ticks, flattening and encoding a png is too slow to do for
+// each of many ticks in a frame, and the page only ever shows how the frame ends anyway
+pub fn flush() {
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        if recorder.recording && recorder.pending {
+            recorder.capture();
+        }
+        recorder.pending = false;
+    })
+}
+
+pub fn every(_universe: &Universe) -> f64 {
+    RECORDER.with(|recorder| recorder.borrow().every as f64)
+}
+
+pub fn set_every(_universe: &mut Universe, value: f64) {
+    RECORDER.with(|recorder| recorder.borrow_mut().every = u64::max(1, value as u64))
+}
+
+pub fn frames_per_second(_universe: &Universe) -> f64 {
+    RECORDER.with(|recorder| recorder.borrow().frames_per_second)
+}
+
+pub fn set_frames_per_second(_universe: &mut Universe, value: f64) {
+    RECORDER.with(|recorder| recorder.borrow_mut().frames_per_second = f64::max(1.0, value))
+}
+
+pub fn duration(_universe: &Universe) -> f64 {
+    RECORDER.with(|recorder| recorder.borrow().duration)
+}
+
+pub fn set_duration(_universe: &mut Universe, value: f64) {
+    RECORDER.with(|recorder| recorder.borrow_mut().duration = f64::max(1.0, value))
+}
+
+pub fn save_gif(_universe: &mut Universe) {
+    RECORDER.with(|recorder| {
+        let recorder = recorder.borrow();
+        match &recorder.animation {
+            Some(animation) if !animation.is_empty() => {
+                match animation.to_gif(recorder.frames_per_second) {
+                    Ok(gif) => download_bytes(&gif, "image/gif", &named(".gif")),
+                    Err(error) => log(&format!("Couldn't make a gif, {}", error)),
+                }
+            }
+            _ => log("Nothing has been recorded"),
+        }
+    })
+}
+
+pub fn save_frames(_universe: &mut Universe) {
+    RECORDER.with(|recorder| match &recorder.borrow().animation {
+        // one download, a browser asks about or blocks one per frame
+        Some(animation) if !animation.is_empty() => download_bytes(
+            &animation.to_zip(&named("")),
+            "application/zip",
+            &named("-frames.zip"),
+        ),
+        _ => log("Nothing has been recorded"),
+    })
+}
@@ -0,0 +1,594 @@
+use crate::action::{Action, Parameter};
+use crate::background::{parse_color, to_hex, ALL_FILLS};
+use crate::blend::{Blend, ALL_BLENDS};
+use crate::circle::{ColorMode, Universe};
+use crate::stroke::{Dash, Stroke, StrokeColor, ALL_DASHES, ALL_STROKE_COLORS};
+use crate::{background_ui, overlay, recording, size_ui};
+
+// where a tunable goes in the control panel
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Group {
+    SIMULATION,
+    BRUSH,
+    COLOR,
+    APPLES,
+    EXPORT,
+}
+
+pub const ALL_GROUPS: [Group; 5] = [
+    Group::SIMULATION,
+    Group::BRUSH,
+    Group::COLOR,
+    Group::APPLES,
+    Group::EXPORT,
+];
+
+impl Group {
+    pub fn name(self) -> &'static str {
+        match self {
+            Group::SIMULATION => "simulation",
+            Group::BRUSH => "brush",
+            Group::COLOR => "color",
+            Group::APPLES => "apples",
+            Group::EXPORT => "export",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Group::SIMULATION => "Simulation",
+            Group::BRUSH => "Brush",
+            Group::COLOR => "Color",
+            Group::APPLES => "Apples",
+            Group::EXPORT => "Export",
+        }
+    }
+}
+
+// how a tunable is read and changed, setters go through Universe::perform so they are logged
+#[derive(Copy, Clone)]
+pub enum Kind {
+    RANGE {
+        min: f64,
+        max: f64,
+        step: f64,
+        get: fn(&Universe) -> f64,
+        set: fn(&mut Universe, f64),
+    },
+    TOGGLE {
+        get: fn(&Universe) -> bool,
+        set: fn(&mut Universe, bool),
+    },
+    // one of a few named values
+    CHOICE {
+        names: fn() -> Vec<&'static str>,
+        get: fn(&Universe) -> &'static str,
+        set: fn(&mut Universe, &str),
+    },
+    COLOR {
+        get: fn(&Universe) -> [u8; 3],
+        set: fn(&mut Universe, [u8; 3]),
+    },
+}
+
+// something about the universe that can be tuned, everything that shows it or saves it is made from this
+#[derive(Copy, Clone)]
+pub struct Tunable {
+    // used in links, presets and the keymap, so it shouldn't change
+    pub name: &'static str,
+    pub title: &'static str,
+    pub label: Option<&'static str>,
+    pub group: Group,
+    pub kind: Kind,
+    // the key that clicks it, for toggles and choices
+    pub key: Option<&'static str>,
+    pub undoable: bool,
+    // kept by the page rather than the universe, so the command line can't set it through one
+    pub page: bool,
+}
+
+impl Tunable {
+    pub fn id(&self) -> String {
+        let suffix = match self.kind {
+            Kind::RANGE { .. } => "slider",
+            Kind::TOGGLE { .. } => "checkbox",
+            Kind::CHOICE { .. } => "choice",
+            Kind::COLOR { .. } => "color",
+        };
+        format!("{}-{}", self.name, suffix)
+    }
+
+    pub fn value(&self, universe: &Universe) -> String {
+        match self.kind {
+            Kind::RANGE { get, .. } => get(universe).to_string(),
+            Kind::TOGGLE { get, .. } => get(universe).to_string(),
+            Kind::CHOICE { get, .. } => String::from(get(universe)),
+            // without the #, which would end a link's query
+            Kind::COLOR { get, .. } => String::from(&to_hex(get(universe))[1..]),
+        }
+    }
+
+    // ranges are clamped rather than refused, so old links keep working when a range shrinks
+    pub fn set_value(&self, universe: &mut Universe, value: &str) -> Result<(), String> {
+        let unknown = || format!("{} can't be {:?}", self.name, value);
+        match self.kind {
+            Kind::RANGE { min, max, set, .. } => {
+                let value = value.parse::<f64>().map_err(|_| unknown())?;
+                set(universe, f64::min(max, f64::max(min, value)));
+            }
+            Kind::TOGGLE { set, .. } => set(universe, value.parse().map_err(|_| unknown())?),
+            Kind::CHOICE { names, set, .. } => {
+                if !names().contains(&value) {
+                    return Err(unknown());
+                }
+                set(universe, value);
+            }
+            Kind::COLOR { set, .. } => set(universe, parse_color(value).ok_or_else(unknown)?),
+        }
+        Ok(())
+    }
+}
+
+fn range(
+    name: &'static str,
+    title: &'static str,
+    label: Option<&'static str>,
+    group: Group,
+    (min, max, step): (f64, f64, f64),
+    get: fn(&Universe) -> f64,
+    set: fn(&mut Universe, f64),
+) -> Tunable {
+    Tunable {
+        name,
+        title,
+        label,
+        group,
+        kind: Kind::RANGE {
+            min,
+            max,
+            step,
+            get,
+            set,
+        },
+        key: None,
+        undoable: true,
+        page: false,
+    }
+}
+
+fn toggle(
+    name: &'static str,
+    title: &'static str,
+    label: &'static str,
+    group: Group,
+    key: &'static str,
+    get: fn(&Universe) -> bool,
+    set: fn(&mut Universe, bool),
+) -> Tunable {
+    Tunable {
+        name,
+        title,
+        label: Some(label),
+        group,
+        kind: Kind::TOGGLE { get, set },
+        key: Some(key),
+        undoable: false,
+        page: true,
+    }
+}
+
+fn set_stroke(universe: &mut Universe, f: impl FnOnce(&mut Stroke)) {
+    let mut stroke = universe.config.stroke;
+    f(&mut stroke);
+    universe.perform(Action::STROKE(stroke));
+}
+
+// in the order they are shown
+pub fn tunables() -> Vec<Tunable> {
+    vec![
+        Tunable {
+            undoable: false,
+            ..range(
+                "ticks",
+                "Ticks per Frame, Shared by Circles",
+                Some("🏎️"),
+                Group::SIMULATION,
+                (1.0, 5000.0, 1.0),
+                |universe| universe.config.speed.ticks,
+                |universe, value| universe.perform(Action::SET(Parameter::TICKS, value)),
+            )
+        },
+        Tunable {
+            undoable: false,
+            ..range(
+                "budget",
+                "Milliseconds per Frame, 0 for Ticks per Frame",
+                Some("⏱️"),
+                Group::SIMULATION,
+                (0.0, 50.0, 1.0),
+                |universe| universe.config.speed.budget,
+                |universe, value| universe.perform(Action::SET(Parameter::BUDGET, value)),
+            )
+        },
+        range(
+            "distance",
+            "Movement Speed",
+            Some("↔"),
+            Group::SIMULATION,
+            (0.0, 100.0, 1.0),
+            |universe| universe.circle_config.max_position_delta,
+            |universe, value| universe.perform(Action::SET(Parameter::DISTANCE, value)),
+        ),
+        toggle(
+            "outline",
+            "Outline the Running Circles",
+            "🔆",
+            Group::SIMULATION,
+            "l",
+            overlay::outline,
+            overlay::set_outline,
+        ),
+        toggle(
+            "crosshair",
+            "Crosshairs",
+            "➕",
+            Group::SIMULATION,
+            "x",
+            overlay::crosshair,
+            overlay::set_crosshair,
+        ),
+        toggle(
+            "velocity",
+            "Velocity Arrows",
+            "💨",
+            Group::SIMULATION,
+            "v",
+            overlay::velocity,
+            overlay::set_velocity,
+        ),
+        toggle(
+            "labels",
+            "Circle Numbers",
+            "🔢",
+            Group::SIMULATION,
+            "n",
+            overlay::labels,
+            overlay::set_labels,
+        ),
+        toggle(
+            "countdown",
+            "Apple Countdowns",
+            "⏲️",
+            Group::SIMULATION,
+            "c",
+            overlay::countdown,
+            overlay::set_countdown,
+        ),
+        toggle(
+            "trails",
+            "Trails",
+            "👻",
+            Group::SIMULATION,
+            "t",
+            overlay::trails,
+            overlay::set_trails,
+        ),
+        Tunable {
+            undoable: false,
+            page: true,
+            ..range(
+                "trail-length",
+                "Trail Length",
+                Some("👣"),
+                Group::SIMULATION,
+                (1.0, 50.0, 1.0),
+                overlay::trail_length,
+                overlay::set_trail_length,
+            )
+        },
+        Tunable {
+            page: true,
+            ..range(
+                "width",
+                "Canvas Width",
+                Some("↔️"),
+                Group::SIMULATION,
+                (1.0, 10000.0, 1.0),
+                |universe| universe.circle_config.width,
+                |universe, value| {
+                    let height = universe.circle_config.height;
+                    size_ui::resize(universe, value, height);
+                },
+            )
+        },
+        Tunable {
+            page: true,
+            ..range(
+                "height",
+                "Canvas Height",
+                Some("↕️"),
+                Group::SIMULATION,
+                (1.0, 10000.0, 1.0),
+                |universe| universe.circle_config.height,
+                |universe, value| {
+                    let width = universe.circle_config.width;
+                    size_ui::resize(universe, width, value);
+                },
+            )
+        },
+        range(
+            "radius",
+            "Size",
+            None,
+            Group::BRUSH,
+            (1.0, 100.0, 1.0),
+            |universe| universe.config.radius,
+            |universe, value| universe.perform(Action::SET(Parameter::RADIUS, value)),
+        ),
+        Tunable {
+            name: "stroke-color",
+            title: "Stroke Color",
+            label: None,
+            group: Group::BRUSH,
+            kind: Kind::CHOICE {
+                names: || ALL_STROKE_COLORS.iter().map(|color| color.name()).collect(),
+                get: |universe| universe.config.stroke.color.name(),
+                set: |universe, name| {
+                    if let Some(color) = StrokeColor::of_name(name) {
+                        set_stroke(universe, |stroke| stroke.color = color);
+                    }
+                },
+            },
+            key: None,
+            undoable: true,
+            page: false,
+        },
+        range(
+            "stroke-width",
+            "Stroke Width",
+            Some("✏️"),
+            Group::BRUSH,
+            (0.0, 10.0, 0.5),
+            |universe| universe.config.stroke.width,
+            |universe, value| set_stroke(universe, |stroke| stroke.width = value),
+        ),
+        Tunable {
+            name: "dash",
+            title: "Dashes",
+            label: None,
+            group: Group::BRUSH,
+            kind: Kind::CHOICE {
+                names: || ALL_DASHES.iter().map(|dash| dash.name()).collect(),
+                get: |universe| universe.config.stroke.dash.name(),
+                set: |universe, name| {
+                    if let Some(dash) = Dash::of_name(name) {
+                        set_stroke(universe, |stroke| stroke.dash = dash);
+                    }
+                },
+            },
+            key: None,
+            undoable: true,
+            page: false,
+        },
+        Tunable {
+            name: "hollow",
+            title: "Hollow",
+            label: Some("⭕"),
+            group: Group::BRUSH,
+            kind: Kind::TOGGLE {
+                get: |universe| universe.config.stroke.hollow,
+                set: |universe, value| set_stroke(universe, |stroke| stroke.hollow = value),
+            },
+            key: Some("o"),
+            undoable: true,
+            page: false,
+        },
+        Tunable {
+            name: "blend",
+            title: "Blend",
+            label: None,
+            group: Group::BRUSH,
+            kind: Kind::CHOICE {
+                names: || ALL_BLENDS.iter().map(|blend| blend.name()).collect(),
+                get: |universe| universe.config.blend.name(),
+                set: |universe, name| {
+                    if let Some(blend) = Blend::of_name(name) {
+                        universe.perform(Action::BLEND(blend));
+                    }
+                },
+            },
+            key: None,
+            undoable: true,
+            page: false,
+        },
+        range(
+            "color",
+            "Color Speed",
+            Some("🌈"),
+            Group::COLOR,
+            (0.0, 50.0, 1.0),
+            |universe| universe.circle_config.max_color_delta as f64,
+            |universe, value| universe.perform(Action::SET(Parameter::COLOR, value)),
+        ),
+        Tunable {
+            name: "color-mode",
+            title: "Color Mode",
+            label: None,
+            group: Group::COLOR,
+            kind: Kind::CHOICE {
+                names: || vec![ColorMode::RGB.name(), ColorMode::HSL.name()],
+                get: |universe| universe.config.color_mode.name(),
+                set: |universe, name| {
+                    if universe.config.color_mode.name() != name {
+                        universe.perform(Action::COLORMODE);
+                    }
+                },
+            },
+            key: None,
+            undoable: true,
+            page: false,
+        },
+        Tunable {
+            name: "gradient",
+            title: "Background",
+            label: Some("⬜"),
+            group: Group::COLOR,
+            kind: Kind::CHOICE {
+                names: || ALL_FILLS.iter().map(|fill| fill.name()).collect(),
+                get: background_ui::fill,
+                set: background_ui::set_fill,
+            },
+            key: Some("g"),
+            undoable: false,
+            page: true,
+        },
+        Tunable {
+            name: "background",
+            title: "Background Color",
+            label: Some("🎨"),
+            group: Group::COLOR,
+            kind: Kind::COLOR {
+                get: background_ui::color,
+                set: background_ui::set_color,
+            },
+            key: None,
+            undoable: false,
+            page: true,
+        },
+        Tunable {
+            name: "background-to",
+            title: "Background Gradient End",
+            label: Some("➡️"),
+            group: Group::COLOR,
+            kind: Kind::COLOR {
+                get: background_ui::to_color,
+                set: background_ui::set_to_color,
+            },
+            key: None,
+            undoable: false,
+            page: true,
+        },
+        Tunable {
+            undoable: false,
+            page: true,
+            ..range(
+                "grain",
+                "Paper Grain",
+                Some("🌾"),
+                Group::COLOR,
+                (0.0, 1.0, 0.05),
+                background_ui::grain,
+                background_ui::set_grain,
+            )
+        },
+        toggle(
+            "transparent",
+            "Export without the Background",
+            "🏁",
+            Group::COLOR,
+            "k",
+            background_ui::transparent_export,
+            background_ui::set_transparent_export,
+        ),
+        range(
+            "steps",
+            "Steps",
+            Some("👣"),
+            Group::APPLES,
+            (0.0, 10000.0, 100.0),
+            |universe| universe.config.apple_steps as f64,
+            |universe, value| universe.perform(Action::SET(Parameter::STEPS, value)),
+        ),
+        range(
+            "split",
+            "Split Chance",
+            Some("🌳"),
+            Group::APPLES,
+            (0.0, 0.05, 0.001),
+            |universe| universe.config.apple_split_chance,
+            |universe, value| universe.perform(Action::SET(Parameter::SPLIT, value)),
+        ),
+        range(
+            "children",
+            "Children",
+            Some("🌱"),
+            Group::APPLES,
+            (2.0, 5.0, 1.0),
+            |universe| universe.config.apple_children as f64,
+            |universe, value| universe.perform(Action::SET(Parameter::CHILDREN, value)),
+        ),
+        range(
+            "apples",
+            "Max Apples",
+            Some("🧺"),
+            Group::APPLES,
+            (1.0, 200.0, 1.0),
+            |universe| universe.config.max_apples as f64,
+            |universe, value| universe.perform(Action::SET(Parameter::APPLES, value)),
+        ),
+        Tunable {
+            undoable: false,
+            page: true,
+            ..range(
+                "frame-every",
+                "Ticks between Frames",
+                Some("🎞️"),
+                Group::EXPORT,
+                (1.0, 100.0, 1.0),
+                recording::every,
+                recording::set_every,
+            )
+        },
+        Tunable {
+            undoable: false,
+            page: true,
+            ..range(
+                "fps",
+                "Frames per Second",
+                Some("🕒"),
+                Group::EXPORT,
+                (1.0, 50.0, 1.0),
+                recording::frames_per_second,
+                recording::set_frames_per_second,
+            )
+        },
+        Tunable {
+            undoable: false,
+            page: true,
+            ..range(
+                "duration",
+                "Seconds",
+                Some("⌛"),
+                Group::EXPORT,
+                (1.0, 60.0, 1.0),
+                recording::duration,
+                recording::set_duration,
+            )
+        },
+    ]
+}
+
+pub fn tunable(name: &str) -> Option<Tunable> {
+    tunables().into_iter().find(|tunable| tunable.name == name)
+}
+
+// every tunable as name=value pairs joined by &, like a url's query
+pub fn to_query(universe: &Universe) -> String {
+    tunables()
+        .iter()
+        .map(|tunable| format!("{}={}", tunable.name, tunable.value(universe)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+// names it doesn't know are skipped, so links from newer versions still mostly work
+pub fn apply_query(universe: &mut Universe, query: &str) -> Result<(), String> {
+    let query = query.trim_start_matches('?');
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        if let Some(tunable) = tunable(name) {
+            tunable.set_value(universe, value)?;
+        }
+    }
+    Ok(())
+}
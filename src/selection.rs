@@ -0,0 +1,417 @@
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::action::{Action, Edit, Override, Target};
+use crate::background::{parse_color, to_hex};
+use crate::circle::{Circle, Position, Universe};
+use crate::registry::Group;
+use crate::{document, history, layers, sidebar};
+
+const CIRCLE_DIV_ID: &str = "circle-panel";
+const X_INPUT_ID: &str = "inspect-x";
+const Y_INPUT_ID: &str = "inspect-y";
+const RADIUS_INPUT_ID: &str = "inspect-radius";
+const COLOR_INPUT_ID: &str = "inspect-color";
+const STEPS_INPUT_ID: &str = "inspect-steps";
+const COLOR_TEXT_ID: &str = "inspect-color-text";
+const AGE_TEXT_ID: &str = "inspect-age";
+
+// by id, because indexes shift as apples finish and circles are frozen or undone
+#[derive(Copy, Clone)]
+enum Picked {
+    CIRCLE(u64),
+    APPLE(u64),
+}
+
+struct Selection {
+    // the layer and the circle or apple clicked last
+    target: Option<(u32, Picked)>,
+    universe: Option<Arc<Mutex<Universe>>>,
+}
+
+thread_local! {
+    static SELECTION: RefCell<Selection> = RefCell::new(Selection {
+        target: None,
+        universe: None,
+    });
+}
+
+// the selected circle or apple, if it is still there on the layer being drawn on
+pub fn selected(universe: &Universe) -> Option<Target> {
+    SELECTION.with(|selection| match selection.borrow().target {
+        Some((layer, picked)) if layer == layers::active_id() => match picked {
+            Picked::CIRCLE(id) => universe.circle_index(id).map(Target::CIRCLE),
+            Picked::APPLE(id) => universe.apple_index(id).map(Target::APPLE),
+        },
+        _ => None,
+    })
+}
+
+pub fn selected_circle(universe: &Universe) -> Option<&Circle> {
+    match selected(universe)? {
+        Target::CIRCLE(index) => Some(&universe.circles[index]),
+        Target::APPLE(index) => Some(&universe.apples[index].circle),
+    }
+}
+
+pub fn select(target: Option<Target>, universe: &Universe) {
+    let picked = match target {
+        Some(Target::CIRCLE(index)) => universe
+            .circles
+            .get(index)
+            .map(|circle| Picked::CIRCLE(circle.id)),
+        Some(Target::APPLE(index)) => universe
+            .apples
+            .get(index)
+            .map(|apple| Picked::APPLE(apple.circle.id)),
+        None => None,
+    };
+    SELECTION.with(|selection| {
+        selection.borrow_mut().target = picked.map(|picked| (layers::active_id(), picked))
+    });
+    render_panel(universe);
+}
+
+// call once a frame, it forgets a circle that was frozen, undone or left behind on
+// another layer, and keeps the inspector up to date with one that is moving
+pub fn refresh(universe: &Universe) {
+    let lost = SELECTION.with(|selection| selection.borrow().target.is_some())
+        && selected(universe).is_none();
+    if lost {
+        select(None, universe);
+    }
+    show_values(universe);
+}
+
+// the action is made from where the selection is now, not where it was when the panel was drawn
+fn perform(action: impl FnOnce(Target) -> Option<Action>) {
+    let universe = match SELECTION.with(|selection| selection.borrow().universe.clone()) {
+        Some(universe) => universe,
+        None => return,
+    };
+    let mut universe = universe.lock().unwrap();
+    let action = match selected(&universe).and_then(action) {
+        Some(action) => action,
+        None => return,
+    };
+    history::checkpoint(&universe);
+    universe.perform(action);
+    render_panel(&universe);
+}
+
+fn edit(edit: Edit) {
+    perform(|target| Some(Action::EDIT(target, edit)))
+}
+
+// apples follow the universe's settings, so only circles have overrides
+fn override_circle(setting: Override) {
+    perform(|target| match target {
+        Target::CIRCLE(index) => Some(Action::OVERRIDE(index, setting)),
+        Target::APPLE(_) => None,
+    })
+}
+
+fn new_element<T: JsCast>(tag: &str) -> T {
+    document()
+        .create_element(tag)
+        .unwrap()
+        .dyn_into::<T>()
+        .unwrap()
+}
+
+fn new_button(
+    text: &str,
+    title: &str,
+    on_click: impl FnMut() + 'static,
+) -> web_sys::HtmlButtonElement {
+    let button: web_sys::HtmlButtonElement = new_element("button");
+    button.set_inner_text(text);
+    button.set_title(title);
+    let on_click_handler = Closure::wrap(Box::new(on_click) as Box<dyn FnMut()>);
+    button.set_onclick(Some(on_click_handler.as_ref().unchecked_ref()));
+    on_click_handler.forget();
+    button
+}
+
+fn new_label(panel: &web_sys::Element, id: &str, text: &str) {
+    let label: web_sys::HtmlLabelElement = new_element("label");
+    label.set_html_for(id);
+    label.set_inner_text(text);
+    panel.append_child(&label).unwrap();
+}
+
+fn new_field(
+    panel: &web_sys::Element,
+    id: &str,
+    type_: &str,
+    on_change: impl FnMut() + 'static,
+) -> web_sys::HtmlInputElement {
+    let input: web_sys::HtmlInputElement = new_element("input");
+    input.set_id(id);
+    input.set_type(type_);
+    let on_change_handler = Closure::wrap(Box::new(on_change) as Box<dyn FnMut()>);
+    input.set_onchange(Some(on_change_handler.as_ref().unchecked_ref()));
+    on_change_handler.forget();
+    panel.append_child(&input).unwrap();
+    input
+}
+
+fn value_of(id: &str) -> String {
+    match document().get_element_by_id(id) {
+        Some(element) => element
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .value(),
+        None => String::new(),
+    }
+}
+
+fn number_of(id: &str) -> f64 {
+    value_of(id).parse().unwrap_or(0.0)
+}
+
+// leaves alone a field that is being typed in
+fn show_value(id: &str, value: &str) {
+    let element = match document().get_element_by_id(id) {
+        Some(element) => element,
+        None => return,
+    };
+    let focused = document()
+        .active_element()
+        .map_or(false, |active| active == element);
+    if !focused {
+        element
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .set_value(value);
+    }
+}
+
+fn show_text(id: &str, text: &str) {
+    if let Some(element) = document().get_element_by_id(id) {
+        element.set_text_content(Some(text));
+    }
+}
+
+fn show_values(universe: &Universe) {
+    let circle = match selected_circle(universe) {
+        Some(circle) => circle,
+        None => return,
+    };
+    show_value(X_INPUT_ID, &format!("{:.1}", circle.position.x));
+    show_value(Y_INPUT_ID, &format!("{:.1}", circle.position.y));
+    show_value(RADIUS_INPUT_ID, &format!("{:.1}", circle.radius));
+    let rgb = circle.color.to_rgb();
+    show_value(COLOR_INPUT_ID, &to_hex(rgb));
+    let (hue, saturation, lightness) = circle.color.hsl_values();
+    show_text(
+        COLOR_TEXT_ID,
+        &format!(
+            "rgb({}, {}, {}) hsl({:.0}, {:.0}%, {:.0}%) {} α {:.2}",
+            rgb[0],
+            rgb[1],
+            rgb[2],
+            hue,
+            saturation * 100.0,
+            lightness * 100.0,
+            to_hex(rgb),
+            circle.color.opacity()
+        ),
+    );
+    show_text(AGE_TEXT_ID, &format!("🕰️ {}", circle.age));
+    if let Some(Target::APPLE(index)) = selected(universe) {
+        show_value(STEPS_INPUT_ID, &universe.apples[index].steps.to_string());
+    }
+}
+
+// position, size and color, and steps for an apple
+fn inspector(panel: &web_sys::Element, target: Target) {
+    let position = || {
+        edit(Edit::POSITION(Position::at(
+            number_of(X_INPUT_ID),
+            number_of(Y_INPUT_ID),
+        )))
+    };
+    new_label(panel, X_INPUT_ID, "📍");
+    new_field(panel, X_INPUT_ID, "number", position).set_title("x");
+    new_field(panel, Y_INPUT_ID, "number", position).set_title("y");
+
+    new_label(panel, RADIUS_INPUT_ID, "📏");
+    new_field(panel, RADIUS_INPUT_ID, "number", || {
+        edit(Edit::RADIUS(number_of(RADIUS_INPUT_ID)))
+    })
+    .set_step("0.5");
+
+    new_field(panel, COLOR_INPUT_ID, "color", || {
+        if let Some(rgb) = parse_color(&value_of(COLOR_INPUT_ID)) {
+            edit(Edit::COLOR(rgb))
+        }
+    });
+    let color_text: web_sys::HtmlElement = new_element("span");
+    color_text.set_id(COLOR_TEXT_ID);
+    panel.append_child(&color_text).unwrap();
+
+    let age_text: web_sys::HtmlElement = new_element("span");
+    age_text.set_id(AGE_TEXT_ID);
+    age_text.set_title("Ticks it has moved for");
+    panel.append_child(&age_text).unwrap();
+
+    if let Target::APPLE(_) = target {
+        new_label(panel, STEPS_INPUT_ID, "⏲️");
+        new_field(panel, STEPS_INPUT_ID, "number", || {
+            edit(Edit::STEPS(number_of(STEPS_INPUT_ID) as u32))
+        })
+        .set_title("Steps left");
+    }
+}
+
+// a range that overrides one setting of the circle once it is let go
+fn override_slider(
+    panel: &web_sys::Element,
+    name: &str,
+    text: &str,
+    // the most and the step
+    range: (f64, f64),
+    // and whether the circle has its own
+    value: (f64, bool),
+    setting: fn(f64) -> Override,
+) {
+    let ((max, step), (value, own)) = (range, value);
+    let id = format!("circle-{}-slider", name);
+    let label: web_sys::HtmlLabelElement = new_element("label");
+    label.set_html_for(&id);
+    label.set_inner_text(text);
+    panel.append_child(&label).unwrap();
+
+    let slider: web_sys::HtmlInputElement = new_element("input");
+    slider.set_id(&id);
+    slider.set_type("range");
+    slider.set_min("0");
+    slider.set_max(&max.to_string());
+    slider.set_step(&step.to_string());
+    slider.set_value(&value.to_string());
+    slider.set_title(&format!(
+        "{} ({})",
+        value,
+        if own {
+            "this circle's own"
+        } else {
+            "the same as the rest"
+        }
+    ));
+    if own {
+        slider.set_class_name("own");
+    }
+    // on change rather than on input, because changing redraws the panel
+    let slider_clone = slider.clone();
+    let on_change_handler = Closure::wrap(Box::new(move || {
+        let value = slider_clone.value().parse().unwrap_or(0.0);
+        override_circle(setting(value));
+    }) as Box<dyn FnMut()>);
+    slider.set_onchange(Some(on_change_handler.as_ref().unchecked_ref()));
+    on_change_handler.forget();
+    panel.append_child(&slider).unwrap();
+}
+
+fn render_panel(universe: &Universe) {
+    let panel = match document().get_element_by_id(CIRCLE_DIV_ID) {
+        Some(panel) => panel,
+        None => return,
+    };
+    panel.set_inner_html("");
+    let target = match selected(universe) {
+        Some(target) => target,
+        None => {
+            panel.set_attribute("hidden", "").unwrap();
+            return;
+        }
+    };
+    panel.remove_attribute("hidden").unwrap();
+
+    let title: web_sys::HtmlLabelElement = new_element("label");
+    title.set_inner_text(&match target {
+        Target::CIRCLE(index) => format!("🎯 {}", universe.circles[index].id),
+        Target::APPLE(index) => format!("🍎 {}", universe.apples[index].circle.id),
+    });
+    panel.append_child(&title).unwrap();
+    inspector(&panel, target);
+    show_values(universe);
+
+    let universe_clone = SELECTION.with(|selection| selection.borrow().universe.clone());
+    let close_button = new_button("✖️", "Deselect", move || {
+        if let Some(universe) = &universe_clone {
+            select(None, &universe.lock().unwrap());
+        }
+    });
+
+    // apples follow the universe's settings when they are made
+    let index = match target {
+        Target::CIRCLE(index) => index,
+        Target::APPLE(_) => {
+            panel.append_child(&close_button).unwrap();
+            return;
+        }
+    };
+    panel
+        .append_child(&new_element::<web_sys::HtmlBrElement>("br"))
+        .unwrap();
+    let circle = &universe.circles[index];
+    let overrides = &circle.overrides;
+    override_slider(
+        &panel,
+        "distance",
+        "🏃",
+        (50.0, 0.1),
+        match overrides.max_position_delta {
+            Some(value) => (value, true),
+            None => (universe.circle_config.max_position_delta, false),
+        },
+        |value| Override::DISTANCE(Some(value)),
+    );
+    override_slider(
+        &panel,
+        "color",
+        "🌈",
+        (255.0, 1.0),
+        match overrides.max_color_delta {
+            Some(value) => (value as f64, true),
+            None => (universe.circle_config.max_color_delta as f64, false),
+        },
+        |value| Override::COLOR(Some(value as u8)),
+    );
+
+    let radius_change = overrides.radius_change.next();
+    let radius_button = new_button(
+        &overrides.radius_change.to_button_display(),
+        &format!("Make it {}", radius_change.name()),
+        move || override_circle(Override::RADIUS(radius_change)),
+    );
+    panel.append_child(&radius_button).unwrap();
+
+    let color_mode = circle.color.mode().next();
+    let color_mode_button = new_button(
+        &circle.color.mode().to_button_display(),
+        &format!("Change its color as {}", color_mode.name()),
+        move || override_circle(Override::COLORMODE(color_mode)),
+    );
+    panel.append_child(&color_mode_button).unwrap();
+
+    let reset_button = new_button("↩️", "Back to the same as the rest", || {
+        override_circle(Override::RESET)
+    });
+    reset_button.set_disabled(overrides.is_empty());
+    panel.append_child(&reset_button).unwrap();
+    panel.append_child(&close_button).unwrap();
+}
+
+pub fn circle_panel(universe: &Arc<Mutex<Universe>>) -> Result<(), JsValue> {
+    SELECTION.with(|selection| selection.borrow_mut().universe = Some(Arc::clone(universe)));
+    let panel: web_sys::HtmlDivElement = new_element("div");
+    panel.set_id(CIRCLE_DIV_ID);
+    panel.set_class_name("control");
+    sidebar::section(Group::BRUSH).append_child(&panel)?;
+    render_panel(&universe.lock().unwrap());
+    Ok(())
+}
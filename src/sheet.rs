@@ -0,0 +1,180 @@
+use crate::raster::Raster;
+
+// each glyph is five rows of three pixels, the left pixel is the high bit
+const GLYPH_WIDTH: u32 = 3;
+const GLYPH_HEIGHT: u32 = 5;
+const TEXT_SCALE: u32 = 2;
+const MARGIN: u32 = 12;
+const LINE_HEIGHT: u32 = (GLYPH_HEIGHT + 2) * TEXT_SCALE;
+const TEXT_COLOR: [u8; 3] = [40, 40, 40];
+
+fn glyph(character: char) -> [u8; 5] {
+    match character.to_ascii_lowercase() {
+        'a' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'b' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'c' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'd' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'e' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'f' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'g' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'h' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'i' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'j' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'k' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'l' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'm' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'n' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'o' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'p' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'r' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        's' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        't' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'u' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'v' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'w' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'x' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        _ => [0b000; 5],
+    }
+}
+
+pub fn draw_text(raster: &mut Raster, x: u32, y: u32, text: &str) {
+    for (index, character) in text.chars().enumerate() {
+        let left = x + index as u32 * (GLYPH_WIDTH + 1) * TEXT_SCALE;
+        for (row, bits) in glyph(character).iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (0b100 >> column) != 0 {
+                    raster.fill_rect(
+                        left + column * TEXT_SCALE,
+                        y + row as u32 * TEXT_SCALE,
+                        TEXT_SCALE,
+                        TEXT_SCALE,
+                        TEXT_COLOR,
+                    );
+                }
+            }
+        }
+    }
+}
+
+// one picture on the sheet, with the lines of text to write under it
+pub struct Cell {
+    pub raster: Raster,
+    pub caption: Vec<String>,
+}
+
+// lays the cells out in a grid, row by row, each with its caption underneath
+pub fn contact_sheet(cells: &[Cell], columns: usize) -> Raster {
+    let columns = usize::max(1, columns);
+    let rows = (cells.len() + columns - 1) / columns;
+    let cell_width = cells
+        .iter()
+        .map(|cell| cell.raster.width)
+        .max()
+        .unwrap_or(0);
+    let cell_height = cells
+        .iter()
+        .map(|cell| cell.raster.height)
+        .max()
+        .unwrap_or(0);
+    let caption_lines = cells
+        .iter()
+        .map(|cell| cell.caption.len())
+        .max()
+        .unwrap_or(0) as u32;
+    let column_width = cell_width + MARGIN;
+    let row_height = cell_height + caption_lines * LINE_HEIGHT + MARGIN * 2;
+
+    let mut sheet = Raster::new(
+        (MARGIN + columns as u32 * column_width) as f64,
+        (MARGIN + rows as u32 * row_height) as f64,
+        1.0,
+    );
+    sheet.fill_rect(0, 0, sheet.width, sheet.height, [255, 255, 255]);
+
+    for (index, cell) in cells.iter().enumerate() {
+        let x = MARGIN + (index % columns) as u32 * column_width;
+        let y = MARGIN + (index / columns) as u32 * row_height;
+        sheet.paste(&cell.raster, x, y);
+        for (line, text) in cell.caption.iter().enumerate() {
+            draw_text(
+                &mut sheet,
+                x,
+                y + cell.raster.height + MARGIN / 2 + line as u32 * LINE_HEIGHT,
+                text,
+            );
+        }
+    }
+    sheet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(rgb: [u8; 3], caption: &[&str]) -> Cell {
+        let mut raster = Raster::new(10.0, 10.0, 1.0);
+        raster.fill_rect(0, 0, 10, 10, rgb);
+        Cell {
+            raster,
+            caption: caption.iter().map(|line| String::from(*line)).collect(),
+        }
+    }
+
+    fn pixel(raster: &Raster, x: u32, y: u32) -> [u8; 3] {
+        let index = (y as usize * raster.width as usize + x as usize) * 4;
+        let pixel = &raster.pixels()[index..index + 3];
+        [pixel[0], pixel[1], pixel[2]]
+    }
+
+    #[test]
+    fn cells_are_laid_out_row_by_row() {
+        let cells = [
+            cell([255, 0, 0], &["a"]),
+            cell([0, 255, 0], &["b", "c"]),
+            cell([0, 0, 255], &[]),
+        ];
+        let sheet = contact_sheet(&cells, 2);
+        let column_width = 10 + MARGIN;
+        let row_height = 10 + 2 * LINE_HEIGHT + MARGIN * 2;
+        assert_eq!(sheet.width, MARGIN + 2 * column_width);
+        assert_eq!(sheet.height, MARGIN + 2 * row_height);
+        assert_eq!(pixel(&sheet, 0, 0), [255, 255, 255]);
+        assert_eq!(pixel(&sheet, MARGIN, MARGIN), [255, 0, 0]);
+        assert_eq!(pixel(&sheet, MARGIN + column_width, MARGIN), [0, 255, 0]);
+        assert_eq!(pixel(&sheet, MARGIN, MARGIN + row_height), [0, 0, 255]);
+        // the top left pixel of the "a" under the first cell is blank, the middle one is not
+        assert_eq!(
+            pixel(&sheet, MARGIN, MARGIN + 10 + MARGIN / 2),
+            [255, 255, 255]
+        );
+        assert_eq!(
+            pixel(&sheet, MARGIN + TEXT_SCALE, MARGIN + 10 + MARGIN / 2),
+            TEXT_COLOR
+        );
+    }
+
+    #[test]
+    fn no_columns_is_one_column() {
+        let sheet = contact_sheet(&[cell([0, 0, 0], &[]), cell([0, 0, 0], &[])], 0);
+        assert_eq!(sheet.width, MARGIN + 10 + MARGIN);
+        assert_eq!(sheet.height, MARGIN + 2 * (10 + MARGIN * 2));
+    }
+}
@@ -0,0 +1,172 @@
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::registry::{Group, ALL_GROUPS};
+use crate::{body, document, storage};
+
+const SIDEBAR_ID: &str = "sidebar";
+pub const HIDE_BUTTON_ID: &str = "hide-button";
+const STORAGE_KEY: &str = "sidebar";
+// on the page's body, css hides everything but the canvases and the hide button
+const CLEAN_CLASS: &str = "clean";
+
+struct Sidebar {
+    collapsed: Vec<Group>,
+    hidden: bool,
+}
+
+thread_local! {
+    static SIDEBAR: RefCell<Sidebar> = RefCell::new(load());
+}
+
+// saved as "hidden" or "collapsed <group>" lines
+fn load() -> Sidebar {
+    let mut sidebar = Sidebar {
+        collapsed: vec![],
+        hidden: false,
+    };
+    let saved = storage().and_then(|storage| storage.get_item(STORAGE_KEY).unwrap());
+    for line in saved.unwrap_or_default().lines() {
+        match line.split_once(' ') {
+            Some(("collapsed", name)) => {
+                if let Some(group) = ALL_GROUPS.iter().find(|group| group.name() == name) {
+                    sidebar.collapsed.push(*group);
+                }
+            }
+            _ if line == "hidden" => sidebar.hidden = true,
+            _ => {}
+        }
+    }
+    sidebar
+}
+
+fn save(sidebar: &Sidebar) {
+    let mut lines: Vec<String> = sidebar
+        .collapsed
+        .iter()
+        .map(|group| format!("collapsed {}", group.name()))
+        .collect();
+    if sidebar.hidden {
+        lines.push(String::from("hidden"));
+    }
+    if let Some(storage) = storage() {
+        storage.set_item(STORAGE_KEY, &lines.join("\n")).unwrap();
+    }
+}
+
+fn update(f: impl FnOnce(&mut Sidebar)) {
+    SIDEBAR.with(|sidebar| {
+        let mut sidebar = sidebar.borrow_mut();
+        f(&mut sidebar);
+        save(&sidebar);
+    });
+    show();
+}
+
+fn header_id(group: Group) -> String {
+    format!("{}-header", group.name())
+}
+
+fn section_id(group: Group) -> String {
+    format!("{}-section", group.name())
+}
+
+fn show() {
+    SIDEBAR.with(|sidebar| {
+        let sidebar = sidebar.borrow();
+        for group in ALL_GROUPS.iter().copied() {
+            let collapsed = sidebar.collapsed.contains(&group);
+            if let Some(header) = document().get_element_by_id(&header_id(group)) {
+                let arrow = if collapsed { "▸" } else { "▾" };
+                header
+                    .dyn_into::<web_sys::HtmlElement>()
+                    .unwrap()
+                    .set_inner_text(&format!("{} {}", arrow, group.title()));
+            }
+            if let Some(section) = document().get_element_by_id(&section_id(group)) {
+                section
+                    .dyn_into::<web_sys::HtmlElement>()
+                    .unwrap()
+                    .set_hidden(collapsed);
+            }
+        }
+        body()
+            .class_list()
+            .toggle_with_force(CLEAN_CLASS, sidebar.hidden)
+            .unwrap();
+        if let Some(button) = document().get_element_by_id(HIDE_BUTTON_ID) {
+            button
+                .dyn_into::<web_sys::HtmlElement>()
+                .unwrap()
+                .set_inner_text(if sidebar.hidden { "☰" } else { "✕" });
+        }
+    });
+}
+
+pub fn toggle_hidden() {
+    update(|sidebar| sidebar.hidden = !sidebar.hidden);
+}
+
+fn toggle_collapsed(group: Group) {
+    update(|sidebar| {
+        if sidebar.collapsed.contains(&group) {
+            sidebar.collapsed.retain(|collapsed| *collapsed != group);
+        } else {
+            sidebar.collapsed.push(group);
+        }
+    });
+}
+
+fn new_element<T: JsCast>(tag: &str) -> T {
+    document()
+        .create_element(tag)
+        .unwrap()
+        .dyn_into::<T>()
+        .unwrap()
+}
+
+fn sidebar() -> web_sys::HtmlDivElement {
+    if let Some(element) = document().get_element_by_id(SIDEBAR_ID) {
+        return element.dyn_into::<web_sys::HtmlDivElement>().unwrap();
+    }
+
+    let hide_button = new_element::<web_sys::HtmlButtonElement>("button");
+    hide_button.set_id(HIDE_BUTTON_ID);
+    hide_button.set_title("Hide / show the controls");
+    let on_click_handler = Closure::wrap(Box::new(toggle_hidden) as Box<dyn FnMut()>);
+    hide_button.set_onclick(Some(on_click_handler.as_ref().unchecked_ref()));
+    on_click_handler.forget();
+    body().append_child(&hide_button).unwrap();
+
+    let sidebar = new_element::<web_sys::HtmlDivElement>("div");
+    sidebar.set_id(SIDEBAR_ID);
+    for group in ALL_GROUPS.iter().copied() {
+        let header = new_element::<web_sys::HtmlButtonElement>("button");
+        header.set_id(&header_id(group));
+        header.set_class_name("section-header");
+        let on_click_handler =
+            Closure::wrap(Box::new(move || toggle_collapsed(group)) as Box<dyn FnMut()>);
+        header.set_onclick(Some(on_click_handler.as_ref().unchecked_ref()));
+        on_click_handler.forget();
+        sidebar.append_child(&header).unwrap();
+
+        let section = new_element::<web_sys::HtmlDivElement>("div");
+        section.set_id(&section_id(group));
+        section.set_class_name("section");
+        sidebar.append_child(&section).unwrap();
+    }
+    body().append_child(&sidebar).unwrap();
+    show();
+    sidebar
+}
+
+// where the controls of a group go
+pub fn section(group: Group) -> web_sys::HtmlDivElement {
+    sidebar();
+    document()
+        .get_element_by_id(&section_id(group))
+        .unwrap()
+        .dyn_into::<web_sys::HtmlDivElement>()
+        .unwrap()
+}
@@ -0,0 +1,111 @@
+// canvas sizes to start a piece at, print sizes are at 300 dpi
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PaperSize {
+    WINDOW,
+    A4,
+    A3,
+    SQUARE,
+    WIDESCREEN,
+    PHONE,
+}
+
+pub const ALL_PAPER_SIZES: [PaperSize; 6] = [
+    PaperSize::WINDOW,
+    PaperSize::A4,
+    PaperSize::A3,
+    PaperSize::SQUARE,
+    PaperSize::WIDESCREEN,
+    PaperSize::PHONE,
+];
+
+impl PaperSize {
+    pub fn name(self) -> &'static str {
+        match self {
+            PaperSize::WINDOW => "window",
+            PaperSize::A4 => "a4",
+            PaperSize::A3 => "a3",
+            PaperSize::SQUARE => "square",
+            PaperSize::WIDESCREEN => "16:9",
+            PaperSize::PHONE => "phone",
+        }
+    }
+
+    pub fn of_name(name: &str) -> Option<PaperSize> {
+        ALL_PAPER_SIZES
+            .iter()
+            .copied()
+            .find(|paper| paper.name() == name)
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            PaperSize::WINDOW => "Window",
+            PaperSize::A4 => "A4",
+            PaperSize::A3 => "A3",
+            PaperSize::SQUARE => "Square",
+            PaperSize::WIDESCREEN => "16:9",
+            PaperSize::PHONE => "Phone wallpaper",
+        }
+    }
+
+    // the window size is only known to whoever asks
+    pub fn size(self, window_width: f64, window_height: f64) -> (f64, f64) {
+        match self {
+            PaperSize::WINDOW => (window_width, window_height),
+            PaperSize::A4 => (2480.0, 3508.0),
+            PaperSize::A3 => (3508.0, 4961.0),
+            PaperSize::SQUARE => (3000.0, 3000.0),
+            PaperSize::WIDESCREEN => (3840.0, 2160.0),
+            PaperSize::PHONE => (1170.0, 2532.0),
+        }
+    }
+
+    pub fn of_size(
+        width: f64,
+        height: f64,
+        window_width: f64,
+        window_height: f64,
+    ) -> Option<PaperSize> {
+        ALL_PAPER_SIZES
+            .iter()
+            .copied()
+            .find(|paper| paper.size(window_width, window_height) == (width, height))
+    }
+}
+
+// how much to shrink a canvas so all of it can be seen in the given space, never more than 1
+pub fn fit(width: f64, height: f64, space_width: f64, space_height: f64) -> f64 {
+    if width <= 0.0 || height <= 0.0 {
+        return 1.0;
+    }
+    f64::min(1.0, f64::min(space_width / width, space_height / height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_only_ever_shrinks() {
+        assert_eq!(fit(100.0, 100.0, 800.0, 600.0), 1.0);
+        assert_eq!(fit(2000.0, 1000.0, 1000.0, 1000.0), 0.5);
+        assert_eq!(fit(1000.0, 4000.0, 1000.0, 1000.0), 0.25);
+        assert_eq!(fit(0.0, 100.0, 10.0, 10.0), 1.0);
+    }
+
+    #[test]
+    fn sizes_are_found_by_what_they_measure() {
+        assert_eq!(
+            PaperSize::of_size(2480.0, 3508.0, 800.0, 600.0),
+            Some(PaperSize::A4)
+        );
+        assert_eq!(
+            PaperSize::of_size(800.0, 600.0, 800.0, 600.0),
+            Some(PaperSize::WINDOW)
+        );
+        assert_eq!(PaperSize::of_size(801.0, 600.0, 800.0, 600.0), None);
+        for paper in ALL_PAPER_SIZES.iter() {
+            assert_eq!(PaperSize::of_name(paper.name()), Some(*paper));
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::action::Action;
+use crate::circle::Universe;
+use crate::size::{self, PaperSize, ALL_PAPER_SIZES};
+use crate::{all_canvases, body, document, history, layers, panel, window};
+
+const SIZE_DIV_ID: &str = "size";
+const CUSTOM: &str = "custom";
+
+struct Sizing {
+    // the size the inputs were last showing
+    shown: (f64, f64),
+    // the canvas grows along with the window, it never shrinks with it
+    follow_window: bool,
+    window_resized: bool,
+}
+
+thread_local! {
+    static SIZING: RefCell<Sizing> = RefCell::new(Sizing {
+        shown: (0.0, 0.0),
+        follow_window: false,
+        window_resized: false,
+    });
+}
+
+fn window_size() -> (f64, f64) {
+    (body().client_width() as f64, body().client_height() as f64)
+}
+
+// the canvases keep their exact size and are only shrunk on screen, so big ones fit the window
+pub fn fit_to_window() {
+    let space_width = window().inner_width().unwrap().as_f64().unwrap();
+    let space_height = window().inner_height().unwrap().as_f64().unwrap();
+    for canvas in all_canvases() {
+        let (width, height) = (canvas.width() as f64, canvas.height() as f64);
+        let scale = size::fit(width, height, space_width, space_height);
+        let style = canvas.style();
+        style
+            .set_property("width", &format!("{}px", width * scale))
+            .unwrap();
+        style
+            .set_property("height", &format!("{}px", height * scale))
+            .unwrap();
+    }
+}
+
+// every layer gets the new size, each in its own log so it replays the same on its own
+pub fn resize(universe: &mut Universe, width: f64, height: f64) {
+    // an empty box is NaN, which no comparison is true for
+    if !(width >= 1.0 && height >= 1.0) {
+        return;
+    }
+    if (width, height) == (universe.circle_config.width, universe.circle_config.height) {
+        return;
+    }
+    universe.perform(Action::RESIZE(width, height));
+    layers::for_each_inactive(|universe, _| universe.perform(Action::RESIZE(width, height)));
+}
+
+fn element<T: JsCast>(id: &str) -> Option<T> {
+    document()
+        .get_element_by_id(id)
+        .map(|element| element.dyn_into::<T>().unwrap())
+}
+
+// call once a frame, replays and undo change the size too
+pub fn refresh(universe: &Universe) {
+    let (width, height) = (universe.circle_config.width, universe.circle_config.height);
+    if SIZING.with(|sizing| sizing.borrow().shown == (width, height)) {
+        return;
+    }
+    SIZING.with(|sizing| sizing.borrow_mut().shown = (width, height));
+
+    let (window_width, window_height) = window_size();
+    if let Some(select) = element::<web_sys::HtmlSelectElement>("paper-size") {
+        select.set_value(
+            PaperSize::of_size(width, height, window_width, window_height)
+                .map_or(CUSTOM, |paper| paper.name()),
+        );
+    }
+    // the width and height are tunables
+    panel::refresh(universe);
+}
+
+pub fn size_div(universe: &Arc<Mutex<Universe>>) -> web_sys::HtmlDivElement {
+    let div = document()
+        .create_element("div")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlDivElement>()
+        .unwrap();
+    div.set_id(SIZE_DIV_ID);
+    div.set_class_name("control");
+
+    let select = document()
+        .create_element("select")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap();
+    select.set_id("paper-size");
+    select.set_title("Canvas Size");
+    for paper in ALL_PAPER_SIZES.iter() {
+        let option = web_sys::HtmlOptionElement::new_with_text_and_value("", paper.name()).unwrap();
+        select.append_child(&option).unwrap();
+    }
+    let option = web_sys::HtmlOptionElement::new_with_text_and_value("Custom", CUSTOM).unwrap();
+    option.set_disabled(true);
+    select.append_child(&option).unwrap();
+
+    let universe_clone = Arc::clone(universe);
+    let select_clone = select.clone();
+    let on_change_handler = Closure::wrap(Box::new(move || {
+        if let Some(paper) = PaperSize::of_name(&select_clone.value()) {
+            let (window_width, window_height) = window_size();
+            let (width, height) = paper.size(window_width, window_height);
+            let mut universe = universe_clone.lock().unwrap();
+            history::checkpoint(&universe);
+            resize(&mut universe, width, height);
+        }
+    }) as Box<dyn FnMut()>);
+    select.set_onchange(Some(on_change_handler.as_ref().unchecked_ref()));
+    on_change_handler.forget();
+    div.append_child(&select).unwrap();
+
+    label_paper_sizes(&select);
+    refresh(&universe.lock().unwrap());
+    div
+}
+
+// the window paper size changes with the window
+fn label_paper_sizes(select: &web_sys::HtmlSelectElement) {
+    let (window_width, window_height) = window_size();
+    for (index, paper) in ALL_PAPER_SIZES.iter().enumerate() {
+        let (width, height) = paper.size(window_width, window_height);
+        if let Some(option) = select.item(index as u32) {
+            option
+                .dyn_into::<web_sys::HtmlOptionElement>()
+                .unwrap()
+                .set_text(&format!("{} ({} × {})", paper.description(), width, height));
+        }
+    }
+}
+
+pub fn follow_window(_universe: &Universe) -> bool {
+    SIZING.with(|sizing| sizing.borrow().follow_window)
+}
+
+pub fn set_follow_window(_universe: &mut Universe, value: bool) {
+    SIZING.with(|sizing| {
+        let mut sizing = sizing.borrow_mut();
+        sizing.follow_window = value;
+        sizing.window_resized = true;
+    });
+}
+
+pub fn listen_for_resize() {
+    let on_resize_handler = Closure::wrap(Box::new(move || {
+        SIZING.with(|sizing| sizing.borrow_mut().window_resized = true);
+    }) as Box<dyn FnMut()>);
+    window().set_onresize(Some(on_resize_handler.as_ref().unchecked_ref()));
+    on_resize_handler.forget();
+}
+
+// call once a frame, window resizes wait for it so a drag of the window edge isn't logged pixel by pixel
+pub fn handle_resize(universe: &mut Universe) {
+    let (resized, follow_window) = SIZING.with(|sizing| {
+        let mut sizing = sizing.borrow_mut();
+        (
+            std::mem::replace(&mut sizing.window_resized, false),
+            sizing.follow_window,
+        )
+    });
+    if !resized {
+        return;
+    }
+
+    fit_to_window();
+    if let Some(select) = element::<web_sys::HtmlSelectElement>("paper-size") {
+        label_paper_sizes(&select);
+    }
+    // the window paper size may have started or stopped matching
+    SIZING.with(|sizing| sizing.borrow_mut().shown = (0.0, 0.0));
+
+    let (window_width, window_height) = window_size();
+    let (width, height) = (universe.circle_config.width, universe.circle_config.height);
+    if follow_window && (window_width > width || window_height > height) {
+        resize(
+            universe,
+            f64::max(width, window_width),
+            f64::max(height, window_height),
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+use wasm_bindgen::JsCast;
+
+use crate::action::{Action, Run};
+use crate::circle::Universe;
+use crate::{document, START_STOP_BUTTON_ID};
+
+struct Stepping {
+    // how many ticks the run button runs for
+    ticks: u64,
+    // what the start / stop button was last showing
+    shown: String,
+}
+
+thread_local! {
+    static STEPPING: RefCell<Stepping> = RefCell::new(Stepping {
+        ticks: 100,
+        shown: String::new(),
+    });
+}
+
+pub fn ticks(_universe: &Universe) -> f64 {
+    STEPPING.with(|stepping| stepping.borrow().ticks as f64)
+}
+
+pub fn set_ticks(_universe: &mut Universe, value: f64) {
+    STEPPING.with(|stepping| stepping.borrow_mut().ticks = value as u64);
+}
+
+pub fn step_once(universe: &mut Universe) {
+    universe.perform(Action::RUN(Run::TICKS(1)));
+}
+
+pub fn run_ticks(universe: &mut Universe) {
+    let ticks = STEPPING.with(|stepping| stepping.borrow().ticks);
+    universe.perform(Action::RUN(Run::TICKS(ticks)));
+}
+
+pub fn run_apples(universe: &mut Universe) {
+    universe.perform(Action::RUN(Run::APPLES));
+}
+
+// runs pause themselves, so the start / stop button is brought up to date every frame
+pub fn refresh(universe: &Universe) {
+    let display = universe.config.status.to_button_display();
+    STEPPING.with(|stepping| {
+        let mut stepping = stepping.borrow_mut();
+        if stepping.shown == display {
+            return;
+        }
+        if let Some(button) = document().get_element_by_id(START_STOP_BUTTON_ID) {
+            button
+                .dyn_into::<web_sys::HtmlElement>()
+                .unwrap()
+                .set_inner_text(&display);
+        }
+        stepping.shown = display;
+    });
+}
@@ -0,0 +1,150 @@
+use crate::circle::Circle;
+
+// inks for StrokeColor::PALETTE, each circle gets the one closest to its fill
+const PALETTE: [[u8; 3]; 6] = [
+    [41, 50, 100],
+    [165, 29, 45],
+    [204, 121, 35],
+    [20, 110, 110],
+    [40, 40, 40],
+    [85, 107, 47],
+];
+
+// where the outline of a circle gets its color from
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StrokeColor {
+    BLACK,
+    FILLCOLOR,
+    DARKER,
+    LIGHTER,
+    COMPLEMENTARY,
+    PALETTE,
+}
+
+pub const ALL_STROKE_COLORS: [StrokeColor; 6] = [
+    StrokeColor::BLACK,
+    StrokeColor::FILLCOLOR,
+    StrokeColor::DARKER,
+    StrokeColor::LIGHTER,
+    StrokeColor::COMPLEMENTARY,
+    StrokeColor::PALETTE,
+];
+
+impl StrokeColor {
+    pub fn name(self) -> &'static str {
+        match self {
+            StrokeColor::BLACK => "black",
+            StrokeColor::FILLCOLOR => "fill",
+            StrokeColor::DARKER => "darker",
+            StrokeColor::LIGHTER => "lighter",
+            StrokeColor::COMPLEMENTARY => "complementary",
+            StrokeColor::PALETTE => "palette",
+        }
+    }
+
+    pub fn of_name(name: &str) -> Option<StrokeColor> {
+        ALL_STROKE_COLORS
+            .iter()
+            .copied()
+            .find(|color| color.name() == name)
+    }
+
+    // the color and opacity of the outline of this circle
+    pub fn rgba(self, circle: &Circle) -> ([u8; 3], f64) {
+        match self {
+            StrokeColor::BLACK => ([0, 0, 0], 1.0),
+            StrokeColor::FILLCOLOR => (circle.color.to_rgb(), circle.color.opacity()),
+            StrokeColor::DARKER => (circle.color.to_slightly_darker_rgb(), 1.0),
+            StrokeColor::LIGHTER => (circle.color.to_slightly_lighter_rgb(), 1.0),
+            StrokeColor::COMPLEMENTARY => (circle.color.to_complementary_rgb(), 1.0),
+            StrokeColor::PALETTE => {
+                let fill = circle.color.to_rgb();
+                let distance = |ink: &[u8; 3]| -> i32 {
+                    (0..3)
+                        .map(|channel| (ink[channel] as i32 - fill[channel] as i32).pow(2))
+                        .sum()
+                };
+                (
+                    *PALETTE.iter().min_by_key(|ink| distance(ink)).unwrap(),
+                    1.0,
+                )
+            }
+        }
+    }
+
+    pub fn css(self, circle: &Circle) -> String {
+        let (rgb, opacity) = self.rgba(circle);
+        format!("rgba({}, {}, {}, {})", rgb[0], rgb[1], rgb[2], opacity)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Dash {
+    SOLID,
+    DASHED,
+    DOTTED,
+    DASHDOT,
+}
+
+pub const ALL_DASHES: [Dash; 4] = [Dash::SOLID, Dash::DASHED, Dash::DOTTED, Dash::DASHDOT];
+
+impl Dash {
+    pub fn name(self) -> &'static str {
+        match self {
+            Dash::SOLID => "solid",
+            Dash::DASHED => "dashed",
+            Dash::DOTTED => "dotted",
+            Dash::DASHDOT => "dashdot",
+        }
+    }
+
+    pub fn of_name(name: &str) -> Option<Dash> {
+        ALL_DASHES.iter().copied().find(|dash| dash.name() == name)
+    }
+
+    // alternating lengths of line and gap, like canvas setLineDash, empty for solid
+    pub fn pattern(self, width: f64) -> Vec<f64> {
+        let unit = f64::max(1.0, width);
+        match self {
+            Dash::SOLID => vec![],
+            Dash::DASHED => vec![6.0 * unit, 4.0 * unit],
+            Dash::DOTTED => vec![unit, 2.0 * unit],
+            Dash::DASHDOT => vec![8.0 * unit, 3.0 * unit, unit, 3.0 * unit],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stroke {
+    // 0 draws no outline
+    pub width: f64,
+    pub color: StrokeColor,
+    pub dash: Dash,
+    // only the outline, no fill
+    pub hollow: bool,
+}
+
+impl Stroke {
+    // a thin outline the same color as the fill, which is how circles always looked
+    pub fn new() -> Self {
+        Stroke {
+            width: 1.0,
+            color: StrokeColor::FILLCOLOR,
+            dash: Dash::SOLID,
+            hollow: false,
+        }
+    }
+
+    pub fn of_color(color: StrokeColor) -> Self {
+        Stroke {
+            color,
+            ..Stroke::new()
+        }
+    }
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Stroke::new()
+    }
+}
@@ -0,0 +1,123 @@
+pub const MIN_ZOOM: f64 = 0.25;
+pub const MAX_ZOOM: f64 = 8.0;
+
+// how the canvases are moved and scaled on screen, the picture itself is never redrawn for it.
+// points are in screen pixels from where the canvases sit unmoved
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Viewport {
+    pub scale: f64,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Viewport {
+    pub fn new() -> Self {
+        Viewport {
+            scale: 1.0,
+            x: 0.0,
+            y: 0.0,
+        }
+    }
+
+    // keeps whatever is under `at` there
+    pub fn zoom_at(&mut self, at: (f64, f64), factor: f64) {
+        let scale = f64::min(MAX_ZOOM, f64::max(MIN_ZOOM, self.scale * factor));
+        let factor = scale / self.scale;
+        self.x = at.0 - (at.0 - self.x) * factor;
+        self.y = at.1 - (at.1 - self.y) * factor;
+        self.scale = scale;
+    }
+
+    // keeps the point under the old midpoint of the pinch under the new midpoint
+    pub fn pinch(&mut self, from: ((f64, f64), (f64, f64)), to: ((f64, f64), (f64, f64))) {
+        let (from_mid, from_distance) = midpoint_and_distance(from);
+        let (to_mid, to_distance) = midpoint_and_distance(to);
+        if from_distance == 0.0 {
+            return;
+        }
+        self.zoom_at(from_mid, to_distance / from_distance);
+        self.pan(to_mid.0 - from_mid.0, to_mid.1 - from_mid.1);
+    }
+
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        self.x += dx;
+        self.y += dy;
+    }
+
+    pub fn to_screen(&self, point: (f64, f64)) -> (f64, f64) {
+        (self.x + point.0 * self.scale, self.y + point.1 * self.scale)
+    }
+
+    pub fn from_screen(&self, point: (f64, f64)) -> (f64, f64) {
+        (
+            (point.0 - self.x) / self.scale,
+            (point.1 - self.y) / self.scale,
+        )
+    }
+
+    pub fn css_transform(&self) -> String {
+        format!(
+            "translate({}px, {}px) scale({})",
+            self.x, self.y, self.scale
+        )
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport::new()
+    }
+}
+
+fn midpoint_and_distance(points: ((f64, f64), (f64, f64))) -> ((f64, f64), f64) {
+    let ((x1, y1), (x2, y2)) = points;
+    (
+        ((x1 + x2) / 2.0, (y1 + y2) / 2.0),
+        f64::hypot(x1 - x2, y1 - y2),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-9 && (actual.1 - expected.1).abs() < 1e-9,
+            "{:?} isn't {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn zooming_keeps_the_point_under_it() {
+        let mut viewport = Viewport::new();
+        viewport.pan(30.0, -20.0);
+        let at = (200.0, 150.0);
+        let under = viewport.from_screen(at);
+        viewport.zoom_at(at, 2.0);
+        assert_eq!(viewport.scale, 2.0);
+        assert_near(viewport.to_screen(under), at);
+    }
+
+    #[test]
+    fn zoom_stops_at_its_limits() {
+        let mut viewport = Viewport::new();
+        viewport.zoom_at((0.0, 0.0), 100.0);
+        assert_eq!(viewport.scale, MAX_ZOOM);
+        viewport.zoom_at((0.0, 0.0), 0.0001);
+        assert_eq!(viewport.scale, MIN_ZOOM);
+    }
+
+    #[test]
+    fn screen_points_go_there_and_back() {
+        let mut viewport = Viewport::new();
+        viewport.zoom_at((50.0, 80.0), 3.0);
+        viewport.pan(-12.5, 40.0);
+        for point in [(0.0, 0.0), (123.0, 45.0), (-7.5, 900.0)].iter() {
+            assert_near(viewport.from_screen(viewport.to_screen(*point)), *point);
+            assert_near(viewport.to_screen(viewport.from_screen(*point)), *point);
+        }
+    }
+}
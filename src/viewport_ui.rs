@@ -0,0 +1,187 @@
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::circle::Position;
+use crate::viewport::Viewport;
+use crate::{all_canvases, background_ui, body, context, document, layers, overlay_canvas, window};
+
+const MINIMAP_ID: &str = "minimap";
+// the longer side of the minimap
+const MINIMAP_SIZE: f64 = 160.0;
+const WHEEL_ZOOM: f64 = 0.002;
+
+thread_local! {
+    static VIEWPORT: RefCell<Viewport> = RefCell::new(Viewport::new());
+}
+
+fn viewport() -> Viewport {
+    VIEWPORT.with(|viewport| *viewport.borrow())
+}
+
+// where the canvases would be on screen if they weren't moved
+fn origin() -> (f64, f64) {
+    let rect = overlay_canvas().get_bounding_client_rect();
+    let viewport = viewport();
+    (rect.left() - viewport.x, rect.top() - viewport.y)
+}
+
+// canvases bigger than the window are also shrunk by css to fit, before any zoom
+fn fit() -> f64 {
+    let canvas = overlay_canvas();
+    if canvas.width() == 0 {
+        return 1.0;
+    }
+    canvas.offset_width() as f64 / canvas.width() as f64
+}
+
+fn local(client: (f64, f64)) -> (f64, f64) {
+    let origin = origin();
+    (client.0 - origin.0, client.1 - origin.1)
+}
+
+pub fn screen_to_world(client_x: f64, client_y: f64) -> Position {
+    let (x, y) = viewport().from_screen(local((client_x, client_y)));
+    let fit = fit();
+    Position::at(x / fit, y / fit)
+}
+
+pub fn world_to_screen(position: &Position) -> (f64, f64) {
+    let fit = fit();
+    let origin = origin();
+    let (x, y) = viewport().to_screen((position.x * fit, position.y * fit));
+    (origin.0 + x, origin.1 + y)
+}
+
+fn update(f: impl FnOnce(&mut Viewport)) {
+    VIEWPORT.with(|viewport| f(&mut viewport.borrow_mut()));
+    let transform = viewport().css_transform();
+    for canvas in all_canvases() {
+        let style = canvas.style();
+        style.set_property("transform-origin", "0 0").unwrap();
+        style.set_property("transform", &transform).unwrap();
+    }
+}
+
+pub fn zoom_at(client: (f64, f64), factor: f64) {
+    let at = local(client);
+    update(|viewport| viewport.zoom_at(at, factor));
+}
+
+pub fn pinch(from: ((f64, f64), (f64, f64)), to: ((f64, f64), (f64, f64))) {
+    let from = (local(from.0), local(from.1));
+    let to = (local(to.0), local(to.1));
+    update(|viewport| viewport.pinch(from, to));
+}
+
+pub fn pan(dx: f64, dy: f64) {
+    update(|viewport| viewport.pan(dx, dy));
+}
+
+pub fn reset() {
+    update(|viewport| *viewport = Viewport::new());
+}
+
+fn window_size() -> (f64, f64) {
+    (
+        window().inner_width().unwrap().as_f64().unwrap(),
+        window().inner_height().unwrap().as_f64().unwrap(),
+    )
+}
+
+pub fn center_on(position: &Position) {
+    let (x, y) = world_to_screen(position);
+    let (width, height) = window_size();
+    pan(width / 2.0 - x, height / 2.0 - y);
+}
+
+pub fn listen_for_wheel() {
+    let on_wheel_handler = Closure::wrap(Box::new(move |event: web_sys::WheelEvent| {
+        event.prevent_default();
+        let factor = f64::exp(-event.delta_y() * WHEEL_ZOOM);
+        zoom_at((event.client_x() as f64, event.client_y() as f64), factor);
+    }) as Box<dyn FnMut(_)>);
+    overlay_canvas().set_onwheel(Some(on_wheel_handler.as_ref().unchecked_ref()));
+    on_wheel_handler.forget();
+}
+
+fn minimap() -> web_sys::HtmlCanvasElement {
+    if let Some(element) = document().get_element_by_id(MINIMAP_ID) {
+        return element.dyn_into::<web_sys::HtmlCanvasElement>().unwrap();
+    }
+    let canvas = document()
+        .create_element("canvas")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+    canvas.set_id(MINIMAP_ID);
+    canvas.set_title("Click to look there, double click to see everything");
+
+    // a click on the minimap moves the view to the same place on the canvas
+    let canvas_clone = canvas.clone();
+    let on_pointer_down = Closure::wrap(Box::new(move |event: web_sys::PointerEvent| {
+        let world = overlay_canvas();
+        let rect = canvas_clone.get_bounding_client_rect();
+        let scale = world.width() as f64 / rect.width();
+        center_on(&Position::at(
+            (event.client_x() as f64 - rect.left()) * scale,
+            (event.client_y() as f64 - rect.top()) * scale,
+        ));
+    }) as Box<dyn FnMut(_)>);
+    canvas.set_onpointerdown(Some(on_pointer_down.as_ref().unchecked_ref()));
+    on_pointer_down.forget();
+
+    let on_double_click = Closure::wrap(Box::new(reset) as Box<dyn FnMut()>);
+    canvas.set_ondblclick(Some(on_double_click.as_ref().unchecked_ref()));
+    on_double_click.forget();
+
+    body().append_child(&canvas).unwrap();
+    canvas
+}
+
+// call once a frame, it only shows when part of the canvas is out of view
+pub fn draw_minimap() {
+    let world = overlay_canvas();
+    let (world_width, world_height) = (world.width() as f64, world.height() as f64);
+    let (width, height) = window_size();
+    let top_left = screen_to_world(0.0, 0.0);
+    let bottom_right = screen_to_world(width, height);
+    let minimap = minimap();
+    let everything_seen = top_left.x <= 0.0
+        && top_left.y <= 0.0
+        && bottom_right.x >= world_width
+        && bottom_right.y >= world_height;
+    minimap.set_hidden(everything_seen || world_width == 0.0 || world_height == 0.0);
+    if minimap.hidden() {
+        return;
+    }
+
+    let scale = MINIMAP_SIZE / f64::max(world_width, world_height);
+    let (map_width, map_height) = (
+        (world_width * scale).round() as u32,
+        (world_height * scale).round() as u32,
+    );
+    if minimap.width() != map_width || minimap.height() != map_height {
+        minimap.set_width(map_width);
+        minimap.set_height(map_height);
+    }
+
+    let context = context(&minimap);
+    context.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0).unwrap();
+    context.clear_rect(0.0, 0.0, map_width as f64, map_height as f64);
+    context.scale(scale, scale).unwrap();
+    context
+        .draw_image_with_html_canvas_element(&background_ui::canvas(), 0.0, 0.0)
+        .unwrap();
+    layers::paint(&context);
+
+    context.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0).unwrap();
+    context.set_stroke_style(&JsValue::from_str("crimson"));
+    context.set_line_width(2.0);
+    context.stroke_rect(
+        top_left.x * scale,
+        top_left.y * scale,
+        (bottom_right.x - top_left.x) * scale,
+        (bottom_right.y - top_left.y) * scale,
+    );
+}